@@ -6,18 +6,21 @@
 use cfgrammar::yacc::YaccKind;
 use lrlex::{CTLexerBuilder, DefaultLexerTypes};
 use lrpar::{unstable_api::UnstableApi, CTParser, CTParserBuilder};
+use std::{env, path::PathBuf};
 
 /// function building the .rs files corresponding to the parser/lexer.
 /// it is precised in `Cargo.toml` that it should be called before building.
 /// the if let pattern is shown as irrefutable, even if it is not...
 #[allow(irrefutable_let_patterns)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // the parser built to look for conflicts is generated, keep it out of the tree
+    let conflicts = PathBuf::from(env::var("OUT_DIR")?).join("conflicts.rs");
     let parser : CTParser =
         CTParserBuilder::<DefaultLexerTypes>::new()
         .error_on_conflicts(false)
         .grammar_path("src/frontend/parser.y")
         .yacckind(YaccKind::Grmtools)
-        .output_path("conflicts.rs")
+        .output_path(conflicts)
         .build()?;
 
     /*
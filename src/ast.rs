@@ -34,6 +34,20 @@ pub enum CompareOp {
     EQ,
 }
 
+impl CompareOp {
+    /// the comparison holding exactly when `self` does not.
+    pub fn negate(&self) -> Self {
+        match self {
+            CompareOp::EQ => CompareOp::NE,
+            CompareOp::NE => CompareOp::EQ,
+            CompareOp::LT => CompareOp::GE,
+            CompareOp::LE => CompareOp::GT,
+            CompareOp::GT => CompareOp::LE,
+            CompareOp::GE => CompareOp::LT,
+        }
+    }
+}
+
 /// binary operands for the type bool.
 #[derive(Debug, Clone, Copy)]
 pub enum BoolBinaryOp {
@@ -88,6 +102,19 @@ impl IntExpr {
                 => span,
         }
     }
+
+    /// pushes the integer literals of the expression in `res`.
+    pub fn constants(&self, res : &mut Vec<i64>) {
+        match self {
+            IntExpr::Unary { exp, .. } => exp.constants(res),
+            IntExpr::Binary { lhs, rhs, .. } | IntExpr::Rand { lower: lhs, upper: rhs, .. } => {
+                lhs.constants(res);
+                rhs.constants(res);
+            },
+            IntExpr::Const { cst, .. } => res.extend(cst.parse::<i64>()),
+            IntExpr::Ident { .. } => (),
+        }
+    }
 }
 
 /// nodes inside a boolean expression.
@@ -127,6 +154,52 @@ impl BoolExpr {
                 => span,
         }
     }
+
+    /// pushes the integer literals of the compared expressions in `res`.
+    pub fn constants(&self, res : &mut Vec<i64>) {
+        match self {
+            BoolExpr::Unary { exp, .. } => exp.constants(res),
+            BoolExpr::Binary { lhs, rhs, .. } => {
+                lhs.constants(res);
+                rhs.constants(res);
+            },
+            BoolExpr::Compare { lhs, rhs, .. } => {
+                lhs.constants(res);
+                rhs.constants(res);
+            },
+            BoolExpr::Const { .. } => (),
+        }
+    }
+
+    /// negation normal form of the expression, or of its negation when `positive`
+    /// is false : the negations are pushed down to the comparisons and constants
+    /// by De Morgan's laws, and disappear.
+    pub fn nnf(&self, positive : bool) -> Self {
+        match self {
+            BoolExpr::Unary { op: BoolUnaryOp::Not, exp, .. }
+                => exp.nnf(!positive),
+            BoolExpr::Binary { span, op, lhs, rhs } => {
+                let op = match (op, positive) {
+                    (BoolBinaryOp::And, true) | (BoolBinaryOp::Or, false) => BoolBinaryOp::And,
+                    (BoolBinaryOp::Or, true) | (BoolBinaryOp::And, false) => BoolBinaryOp::Or,
+                };
+                BoolExpr::Binary {
+                    span: *span,
+                    op,
+                    lhs: Box::new(lhs.nnf(positive)),
+                    rhs: Box::new(rhs.nnf(positive)),
+                }
+            },
+            BoolExpr::Compare { span, op, lhs, rhs } => BoolExpr::Compare {
+                span: *span,
+                op: if positive { *op } else { op.negate() },
+                lhs: lhs.clone(),
+                rhs: rhs.clone(),
+            },
+            BoolExpr::Const { span, cst }
+                => BoolExpr::Const { span: *span, cst: *cst == positive },
+        }
+    }
 }
 
 /// enumeration type for all the nodes of the
@@ -135,6 +208,7 @@ impl BoolExpr {
 #[derive(Debug, Clone)]
 pub enum TNode {
     Block {
+        span: Span,
         decl: Vec<Symbol>,
         stmt: Vec<TNode>,
     },
@@ -151,28 +225,45 @@ pub enum TNode {
         cond: BoolExpr,
         body: Box<TNode>,
     },
-    Halt,
+    Halt {
+        span: Span,
+    },
     Assert {
         cond: BoolExpr,
     },
     Print {
+        span: Span,
         vars: Vec<Symbol>,
     }
 }
 
 impl TNode {
     /// getter for the span of an AST node.
-    pub fn get_span(&self) -> Option<&Span> {
+    pub fn get_span(&self) -> &Span {
         match self {
             TNode::Assign { lhs: _, rhs }
-                => Some(rhs.get_span()),
+                => rhs.get_span(),
             TNode::If { cond, then: _, otherwise: _ }
-                => Some(cond.get_span()),
+                => cond.get_span(),
             TNode::While { cond, body: _ }
-                => Some(cond.get_span()),
+                => cond.get_span(),
             TNode::Assert { cond }
-                => Some(cond.get_span()),
-            _ => None
+                => cond.get_span(),
+            TNode::Print { span, .. } | TNode::Block { span, .. } | TNode::Halt { span }
+                => span,
+        }
+    }
+
+    /// number of statements of the node, itself included.
+    pub fn size(&self) -> usize {
+        match self {
+            TNode::Block { stmt, .. }
+                => 1 + stmt.iter().map(TNode::size).sum::<usize>(),
+            TNode::If { cond: _, then, otherwise }
+                => 1 + then.size() + otherwise.as_ref().map_or(0, |o| o.size()),
+            TNode::While { cond: _, body }
+                => 1 + body.size(),
+            _ => 1
         }
     }
 }
@@ -180,30 +271,68 @@ impl TNode {
 /// type for Programs analyzed by rsabsint.
 pub type Program = Vec<TNode>;
 
+/// statements of a program in preorder : the rank of a statement is
+/// the identifier used by the analysis reports.
+pub fn statements(program: &Program) -> Vec<&TNode> {
+    fn visit<'a>(node: &'a TNode, res: &mut Vec<&'a TNode>) {
+        res.push(node);
+        match node {
+            TNode::Block { stmt, .. } => stmt.iter().for_each(|s| visit(s, res)),
+            TNode::If { cond: _, then, otherwise } => {
+                visit(then, res);
+                if let Some(o) = otherwise {
+                    visit(o, res);
+                }
+            },
+            TNode::While { cond: _, body } => visit(body, res),
+            _ => (),
+        }
+    }
+    let mut res = Vec::new();
+    program.iter().for_each(|s| visit(s, &mut res));
+    res
+}
+
+/// integer literals appearing in the expressions of a program.
+pub fn constants(program: &Program) -> Vec<i64> {
+    let mut res = Vec::new();
+    for stmt in statements(program) {
+        match stmt {
+            TNode::Assign { rhs, .. } => rhs.constants(&mut res),
+            TNode::If { cond, .. } | TNode::While { cond, .. } | TNode::Assert { cond }
+                => cond.constants(&mut res),
+            _ => (),
+        }
+    }
+    res
+}
+
 fn display_symbol(symbol : Symbol, indent: usize) {
     let indentation = " ".repeat(indent);
     match symbol {
-        Symbol::Variable { name, dtype }
+        Symbol::Variable { name, dtype, .. }
             => println!("{}{:?} {};", indentation, dtype, name),
     }
 }
 
-fn format_intexpr(expr: &IntExpr) -> String {
+/// Formats an integer expression.
+pub fn format_intexpr(expr: &IntExpr) -> String {
     match expr {
         IntExpr::Unary { op, exp, .. }
             => format!("{:?}({})", op, format_intexpr(exp)),
         IntExpr::Binary { op, lhs, rhs, .. }
             => format!("({} {:?} {})", format_intexpr(lhs), op, format_intexpr(rhs)),
         IntExpr::Ident { var, .. }
-            => format!("{}", var.get_name()),
+            => var.get_name().to_string(),
         IntExpr::Const { cst, .. }
-            => format!("{}", cst),
+            => cst.to_string(),
         IntExpr::Rand { lower, upper, .. }
             => format!("rand({}, {})", format_intexpr(lower), format_intexpr(upper)),
     }
 }
 
-fn format_boolexpr(expr: &BoolExpr) -> String {
+/// Formats a boolean expression.
+pub fn format_boolexpr(expr: &BoolExpr) -> String {
     match expr {
         BoolExpr::Unary { op, exp, .. }
             => format!("{:?}({})", op, format_boolexpr(exp)),
@@ -220,7 +349,7 @@ fn format_boolexpr(expr: &BoolExpr) -> String {
 pub fn display_tnode(node: &TNode, indent: usize) {
     let indentation = " ".repeat(indent);
     match node {
-        TNode::Block { decl, stmt } => {
+        TNode::Block { decl, stmt, .. } => {
             println!("{}{{", indentation);
             for d in decl {
                 display_symbol(d.clone(), indent + 4);
@@ -245,13 +374,13 @@ pub fn display_tnode(node: &TNode, indent: usize) {
             println!("{}while ({})", indentation, format_boolexpr(cond));
             display_tnode(body, indent + 4);
         }
-        TNode::Halt => {
+        TNode::Halt { .. } => {
             println!("{}halt;", indentation);
         }
         TNode::Assert { cond } => {
             println!("{}assert({});", indentation, format_boolexpr(cond));
         }
-        TNode::Print { vars } => {
+        TNode::Print { vars, .. } => {
             let var_names = 
                 vars
                 .iter()
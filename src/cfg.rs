@@ -0,0 +1,268 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+//! control-flow graph of a program : its nodes are the program points, and
+//! its edges are labelled with the instructions leading from a point to the next.
+use std::fmt;
+use lrpar::Span;
+use crate::ast::*;
+use crate::symbol::Symbol;
+
+/// program points, numbered from 0.
+pub type Point = usize;
+
+/// instructions labelling the edges of the graph.
+#[derive(Debug, Clone)]
+pub enum Instr {
+    /// assignment of an expression to a variable.
+    Assign {
+        var : Symbol,
+        expr : IntExpr,
+    },
+    /// filter keeping the states where the condition evaluates to `value`.
+    Guard {
+        cond : BoolExpr,
+        value : bool,
+    },
+    /// entry of a variable declared by a block.
+    Enter(Symbol),
+    /// exit of a variable when leaving its block.
+    Exit(Symbol),
+    /// check of an assertion, the states where it holds going on.
+    Assert(BoolExpr),
+    /// display of the values of some variables.
+    Print(Vec<Symbol>),
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instr::Assign { var, expr } => write!(f, "{} = {}", var.get_name(), format_intexpr(expr)),
+            Instr::Guard { cond, value : true } => write!(f, "[{}]", format_boolexpr(cond)),
+            Instr::Guard { cond, value : false } => write!(f, "[!{}]", format_boolexpr(cond)),
+            Instr::Enter(var) => write!(f, "enter {}", var.get_name()),
+            Instr::Exit(var) => write!(f, "exit {}", var.get_name()),
+            Instr::Assert(cond) => write!(f, "assert({})", format_boolexpr(cond)),
+            Instr::Print(vars) => {
+                let names : Vec<&str> = vars.iter().map(|v| v.get_name()).collect();
+                write!(f, "print({})", names.join(", "))
+            },
+        }
+    }
+}
+
+/// edge of the graph from `src` to `dst`, with the span of the statement
+/// it comes from (the whole block for the entries and exits of variables).
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub src : Point,
+    pub dst : Point,
+    pub instr : Instr,
+    pub span : Span,
+}
+
+/// control-flow graph, whose points are 0 to `size - 1`. The heads of the
+/// loops are marked : every cycle goes through one of them.
+#[derive(Debug, Clone)]
+pub struct Cfg {
+    pub size : usize,
+    pub entry : Point,
+    pub exit : Point,
+    pub edges : Vec<Edge>,
+    pub loop_heads : Vec<Point>,
+}
+
+impl Cfg {
+    /// integer literals appearing in the instructions of the graph.
+    pub fn constants(&self) -> Vec<i64> {
+        let mut res = Vec::new();
+        for e in &self.edges {
+            match &e.instr {
+                Instr::Assign { expr, .. } => expr.constants(&mut res),
+                Instr::Guard { cond, .. } | Instr::Assert(cond) => cond.constants(&mut res),
+                _ => (),
+            }
+        }
+        res
+    }
+
+    /// lowers a program : its statements run one after the other from the entry.
+    /// The point following a `halt` has no predecessor.
+    pub fn of_program(program : &Program) -> Self {
+        let mut cfg = Cfg { size : 1, entry : 0, exit : 0, edges : Vec::new(), loop_heads : Vec::new() };
+        cfg.exit = program.iter().fold(cfg.entry, |p, stmt| cfg.lower(stmt, p));
+        cfg.compact();
+        cfg
+    }
+
+    fn fresh(&mut self) -> Point {
+        self.size += 1;
+        self.size - 1
+    }
+
+    /// adds an edge from `src` to a new point, which is returned.
+    fn edge(&mut self, src : Point, instr : Instr, span : Span) -> Point {
+        let dst = self.fresh();
+        self.edges.push(Edge { src, dst, instr, span });
+        dst
+    }
+
+    /// merges the point `p` into `q`.
+    fn merge(&mut self, p : Point, q : Point) {
+        for e in self.edges.iter_mut() {
+            if e.src == p {
+                e.src = q;
+            }
+            if e.dst == p {
+                e.dst = q;
+            }
+        }
+    }
+
+    /// adds the edges of `node` from the point `src`, and returns the point
+    /// reached at its end, which is a new one unless `node` is an empty block.
+    fn lower(&mut self, node : &TNode, src : Point) -> Point {
+        let span = *node.get_span();
+        match node {
+            TNode::Block { decl, stmt, .. } => {
+                let p = decl.iter().fold(src, |p, v| self.edge(p, Instr::Enter(v.clone()), span));
+                let p = stmt.iter().fold(p, |p, s| self.lower(s, p));
+                decl.iter().rev().fold(p, |p, v| self.edge(p, Instr::Exit(v.clone()), span))
+            },
+            TNode::Assign { lhs, rhs } =>
+                self.edge(src, Instr::Assign { var : lhs.clone(), expr : rhs.clone() }, span),
+            TNode::If { cond, then, otherwise } => {
+                let p = self.edge(src, Instr::Guard { cond : cond.clone(), value : true }, span);
+                let then_end = self.lower(then, p);
+                let q = self.edge(src, Instr::Guard { cond : cond.clone(), value : false }, span);
+                let else_end = otherwise.as_ref().map_or(q, |o| self.lower(o, q));
+                self.merge(else_end, then_end);
+                then_end
+            },
+            TNode::While { cond, body } => {
+                // the loop head is `src`, where the body comes back
+                self.loop_heads.push(src);
+                let p = self.edge(src, Instr::Guard { cond : cond.clone(), value : true }, span);
+                let body_end = self.lower(body, p);
+                self.merge(body_end, src);
+                self.edge(src, Instr::Guard { cond : cond.clone(), value : false }, span)
+            },
+            // no edge leads to the point after a halt, whose statements keep their own spans
+            TNode::Halt { .. } => self.fresh(),
+            TNode::Assert { cond } =>
+                self.edge(src, Instr::Assert(cond.clone()), span),
+            TNode::Print { vars, .. } =>
+                self.edge(src, Instr::Print(vars.clone()), span),
+        }
+    }
+
+    /// renumbers the points left by the merges, in increasing order.
+    fn compact(&mut self) {
+        let mut used = vec![false; self.size];
+        used[self.entry] = true;
+        used[self.exit] = true;
+        for e in &self.edges {
+            used[e.src] = true;
+            used[e.dst] = true;
+        }
+        let mut number = vec![0; self.size];
+        let mut size = 0;
+        for p in 0..self.size {
+            if used[p] {
+                number[p] = size;
+                size += 1;
+            }
+        }
+        for e in self.edges.iter_mut() {
+            e.src = number[e.src];
+            e.dst = number[e.dst];
+        }
+        self.loop_heads.iter_mut().for_each(|p| *p = number[*p]);
+        self.entry = number[self.entry];
+        self.exit = number[self.exit];
+        self.size = size;
+    }
+
+    /// edges leaving the point `p`.
+    pub fn successors(&self, p : Point) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.src == p)
+    }
+
+    /// edges reaching the point `p`.
+    pub fn predecessors(&self, p : Point) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.dst == p)
+    }
+
+    pub fn is_loop_head(&self, p : Point) -> bool {
+        self.loop_heads.contains(&p)
+    }
+}
+
+impl fmt::Display for Cfg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "entry {}, exit {}", self.entry, self.exit)?;
+        for p in 0..self.size {
+            let head = if self.is_loop_head(p) { " (loop head)" } else { "" };
+            for e in self.successors(p) {
+                writeln!(f, "{}{} -> {} : {}", p, head, e.dst, e.instr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::file_parser::parse_string;
+
+    fn lower(src : &str) -> Cfg {
+        Cfg::of_program(&parse_string(src).unwrap())
+    }
+
+    #[test]
+    fn test_sequence() {
+        let cfg = lower("{ int x; x = 1; print(x); }");
+        assert_eq!(cfg.to_string(), "entry 0, exit 4\n\
+            0 -> 1 : enter x\n\
+            1 -> 2 : x = 1\n\
+            2 -> 3 : print(x)\n\
+            3 -> 4 : exit x\n");
+    }
+
+    #[test]
+    fn test_if() {
+        let cfg = lower("{ int x; if (x < 2) x = 1; else { } assert(x >= 0); }");
+        assert_eq!(cfg.to_string(), "entry 0, exit 5\n\
+            0 -> 1 : enter x\n\
+            1 -> 2 : [(x LT 2)]\n\
+            1 -> 3 : [!(x LT 2)]\n\
+            2 -> 3 : x = 1\n\
+            3 -> 4 : assert((x GE 0))\n\
+            4 -> 5 : exit x\n");
+    }
+
+    #[test]
+    fn test_loops() {
+        let cfg = lower("{ int x; while (x < 10) { while (rand(0,1) == 0) x = x + 1; } halt; }");
+        assert_eq!(cfg.loop_heads, vec![1, 2]);
+        assert_eq!(cfg.to_string(), "entry 0, exit 6\n\
+            0 -> 1 : enter x\n\
+            1 (loop head) -> 2 : [(x LT 10)]\n\
+            1 (loop head) -> 4 : [!(x LT 10)]\n\
+            2 (loop head) -> 3 : [(rand(0, 1) EQ 0)]\n\
+            2 (loop head) -> 1 : [!(rand(0, 1) EQ 0)]\n\
+            3 -> 2 : x = (x Add 1)\n\
+            5 -> 6 : exit x\n");
+    }
+
+    #[test]
+    fn test_spans() {
+        let src = "{ int x; x = 1; while (x < 3) x = x + 1; halt; print(x); }";
+        let cfg = lower(src);
+        let spans : Vec<&str> = cfg.edges.iter().map(|e| &src[e.span.start()..e.span.end()]).collect();
+        assert_eq!(spans, [src, "1", "x < 3", "x + 1", "x < 3", "print(x);", src]);
+    }
+}
@@ -3,65 +3,334 @@
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
  */
-/* 
+use std::collections::{BTreeMap, BTreeSet};
 use crate::ast::*;
 use crate::symbol::*;
-use crate::interpreter::interpreter::*;
+use crate::interpreter::interpreter::AnalysisError;
 
 use super::domain::AbstractDomain;
 
+/// maximal number of environments (and of values of an expression)
+/// tracked before giving up and going to `Top`.
+pub const MAX_STATES : usize = 10_000;
+
+/// maximal number of values of a variable at a loop head : a loop still
+/// growing beyond it goes to `Top`, so that the loops which do not stabilize
+/// are given up after a bounded number of iterations.
+pub const MAX_WIDEN_VALUES : usize = 128;
+
+/// concrete environment, mapping each variable to its value,
+/// or to `None` when it may be any integer.
+type Env = BTreeMap<Symbol, Option<i64>>;
+
+/// concrete collecting domain : the exact set of reachable environments,
+/// or `Top` when there are too many of them to be enumerated.
 #[derive(Clone, PartialEq, Eq)]
 pub enum ConcreteDomain {
-    Caca,
+    Envs(BTreeSet<Env>),
+    Top,
+}
+
+impl ConcreteDomain {
+    /// builds a set of environments, going to `Top` when it is too large.
+    fn of_envs(envs : BTreeSet<Env>) -> Self {
+        if envs.len() > MAX_STATES {
+            ConcreteDomain::Top
+        }
+        else {
+            ConcreteDomain::Envs(envs)
+        }
+    }
+
+    /// set of the values of `e` in `env`, `None` when there are too many of them, when
+    /// some of them do not fit in an `i64` or when `e` reads a variable of unknown value.
+    /// Divisions by zero have no value.
+    fn eval(env : &Env, e : &IntExpr) -> Result<Option<BTreeSet<i64>>, AnalysisError> {
+        let values =
+            match e {
+                IntExpr::Unary { op, exp, .. } => {
+                    let Some(values) = Self::eval(env, exp)? else {
+                        return Ok(None);
+                    };
+                    match op {
+                        IntUnaryOp::AddUnary => Some(values),
+                        IntUnaryOp::SubUnary =>
+                            values.iter().map(|x| x.checked_neg()).collect(),
+                    }
+                },
+                IntExpr::Binary { op, lhs, rhs, .. } => {
+                    let (Some(xs), Some(ys)) = (Self::eval(env, lhs)?, Self::eval(env, rhs)?) else {
+                        return Ok(None);
+                    };
+                    if xs.len().saturating_mul(ys.len()) > MAX_STATES {
+                        return Ok(None);
+                    }
+                    let checked =
+                        match op {
+                            IntBinaryOp::Add => i64::checked_add,
+                            IntBinaryOp::Sub => i64::checked_sub,
+                            IntBinaryOp::Mul => i64::checked_mul,
+                            IntBinaryOp::Div => i64::checked_div,
+                            IntBinaryOp::Mod => i64::checked_rem,
+                        };
+                    let mut values = BTreeSet::new();
+                    for x in &xs {
+                        for y in ys.iter().filter(|y| **y != 0 || !matches!(op, IntBinaryOp::Div | IntBinaryOp::Mod)) {
+                            let Some(v) = checked(*x, *y) else {
+                                return Ok(None);
+                            };
+                            values.insert(v);
+                        }
+                    }
+                    Some(values)
+                },
+                IntExpr::Ident { var, .. } =>
+                    env.get(var).ok_or(AnalysisError::UnknownVariable)?.map(|n| BTreeSet::from([n])),
+                IntExpr::Const { cst, .. } =>
+                    cst.parse().ok().map(|n| BTreeSet::from([n])),
+                IntExpr::Rand { lower, upper, .. } => {
+                    let (Some(ls), Some(us)) = (Self::eval(env, lower)?, Self::eval(env, upper)?) else {
+                        return Ok(None);
+                    };
+                    match (ls.first(), us.last()) {
+                        (Some(l), Some(u)) if u.abs_diff(*l) as usize >= MAX_STATES && l <= u =>
+                            None,
+                        (Some(l), Some(u)) => Some((*l..=*u).collect()),
+                        _ => Some(BTreeSet::new()),
+                    }
+                },
+            };
+        Ok(values.filter(|values| values.len() <= MAX_STATES))
+    }
+
+    /// whether some variable takes more than `MAX_WIDEN_VALUES` values in `envs`.
+    fn too_many_values(envs : &BTreeSet<Env>) -> bool {
+        let mut values : BTreeMap<&Symbol, BTreeSet<i64>> = BTreeMap::new();
+        for env in envs {
+            for (v, n) in env {
+                if let Some(n) = n {
+                    values.entry(v).or_default().insert(*n);
+                }
+            }
+        }
+        values.values().any(|values| values.len() > MAX_WIDEN_VALUES)
+    }
+
+    /// whether `x cmp y` holds.
+    fn holds(x : i64, cmp : &CompareOp, y : i64) -> bool {
+        match cmp {
+            CompareOp::EQ => x == y,
+            CompareOp::NE => x != y,
+            CompareOp::LT => x < y,
+            CompareOp::LE => x <= y,
+            CompareOp::GT => x > y,
+            CompareOp::GE => x >= y,
+        }
+    }
 }
 
 impl AbstractDomain for ConcreteDomain {
+    fn top() -> Self {
+        ConcreteDomain::Envs(BTreeSet::from([Env::new()]))
+    }
+
     fn bottom() -> Self {
-        todo!()
+        ConcreteDomain::Envs(BTreeSet::new())
     }
 
     fn is_bottom(&self) -> bool {
-        todo!()
+        match self {
+            ConcreteDomain::Envs(envs) => envs.is_empty(),
+            ConcreteDomain::Top => false,
+        }
     }
 
     fn subset(&self, rhs : &Self) -> bool {
-        todo!()
+        match (self, rhs) {
+            (_, ConcreteDomain::Top) => true,
+            (ConcreteDomain::Top, _) => false,
+            (ConcreteDomain::Envs(lhs), ConcreteDomain::Envs(rhs)) => lhs.is_subset(rhs),
+        }
     }
 
     fn join_with(&mut self, rhs : Self) {
-        todo!()
+        *self =
+            match (&*self, rhs) {
+                (ConcreteDomain::Envs(lhs), ConcreteDomain::Envs(rhs)) =>
+                    Self::of_envs(lhs.union(&rhs).cloned().collect()),
+                _ => ConcreteDomain::Top,
+            };
     }
 
     fn meet_with(&mut self, rhs : Self) {
-        todo!()
+        match (&*self, rhs) {
+            (ConcreteDomain::Envs(lhs), ConcreteDomain::Envs(rhs)) =>
+                *self = ConcreteDomain::Envs(lhs.intersection(&rhs).cloned().collect()),
+            (ConcreteDomain::Top, rhs) => *self = rhs,
+            (_, ConcreteDomain::Top) => (),
+        }
     }
 
+    // loops either stabilize or overflow `MAX_WIDEN_VALUES` and go to `Top`
     fn widen_with(&mut self, rhs : Self) {
-        todo!()
+        let grows = !rhs.subset(self);
+        self.join_with(rhs);
+        if let ConcreteDomain::Envs(envs) = self {
+            if grows && Self::too_many_values(envs) {
+                *self = ConcreteDomain::Top;
+            }
+        }
     }
 
     fn narrow_with(&mut self, rhs : Self) {
-        todo!()
+        self.meet_with(rhs)
     }
 
     fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self {
-        todo!()
+        let ConcreteDomain::Envs(envs) = self else {
+            return ConcreteDomain::Top;
+        };
+        let mut res = BTreeSet::new();
+        for env in envs.iter() {
+            match (Self::eval(env, e1), Self::eval(env, e2)) {
+                (Ok(Some(xs)), Ok(Some(ys))) => {
+                    if xs.iter().any(|x| ys.iter().any(|y| Self::holds(*x, cmp, *y))) {
+                        res.insert(env.clone());
+                    }
+                },
+                // the comparison may hold for the values which are not enumerated
+                (Ok(_), Ok(_)) => { res.insert(env.clone()); },
+                _ => return ConcreteDomain::Top,
+            }
+        }
+        ConcreteDomain::Envs(res)
     }
 
     fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError> {
-        todo!()
+        let ConcreteDomain::Envs(envs) = self else {
+            return Ok(ConcreteDomain::Top);
+        };
+        let mut res = BTreeSet::new();
+        for env in envs.iter() {
+            if !env.contains_key(v) {
+                return Err(AnalysisError::UnknownVariable);
+            }
+            let values : Vec<Option<i64>> =
+                match Self::eval(env, e)? {
+                    Some(values) => values.into_iter().map(Some).collect(),
+                    None => vec![None],
+                };
+            for value in values {
+                let mut env = env.clone();
+                env.insert(v.clone(), value);
+                res.insert(env);
+            }
+            if res.len() > MAX_STATES {
+                return Ok(ConcreteDomain::Top);
+            }
+        }
+        Ok(ConcreteDomain::Envs(res))
     }
 
     fn add_variable(&mut self, v : &Symbol) -> Self {
-        todo!()
+        match self {
+            ConcreteDomain::Envs(envs) =>
+                ConcreteDomain::Envs(
+                    envs.iter()
+                        .map(|env| {
+                            let mut env = env.clone();
+                            env.insert(v.clone(), None);
+                            env
+                        })
+                        .collect()
+                ),
+            ConcreteDomain::Top => ConcreteDomain::Top,
+        }
     }
 
     fn remove_variable(&mut self, v : &Symbol) -> Self {
-        todo!()
+        match self {
+            ConcreteDomain::Envs(envs) =>
+                ConcreteDomain::Envs(
+                    envs.iter()
+                        .map(|env| {
+                            let mut env = env.clone();
+                            env.remove(v);
+                            env
+                        })
+                        .collect()
+                ),
+            ConcreteDomain::Top => ConcreteDomain::Top,
+        }
+    }
+
+    fn forget(&mut self, v : &Symbol) -> Self {
+        match self {
+            ConcreteDomain::Envs(envs) =>
+                Self::of_envs(
+                    envs.iter()
+                        .map(|env| {
+                            let mut env = env.clone();
+                            if let Some(value) = env.get_mut(v) {
+                                *value = None;
+                            }
+                            env
+                        })
+                        .collect()
+                ),
+            ConcreteDomain::Top => ConcreteDomain::Top,
+        }
     }
 
     fn print(&mut self, symbol : Symbol) -> String {
-        todo!()
+        match self {
+            ConcreteDomain::Envs(envs) if envs.is_empty() => "bot".to_string(),
+            ConcreteDomain::Envs(envs) if envs.iter().any(|env| env.get(&symbol) == Some(&None)) =>
+                "top".to_string(),
+            ConcreteDomain::Envs(envs) => {
+                let values : BTreeSet<i64> =
+                    envs.iter()
+                        .filter_map(|env| env.get(&symbol).copied().flatten())
+                        .collect();
+                let values : Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                format!("{{{}}}", values.join(", "))
+            },
+            ConcreteDomain::Top => "top".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::testing::value_of;
+
+    #[test]
+    fn test_assign() {
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; int y; x = 4; y = 5; x = x + y + 2; }", "x"), "{11}");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = rand(1,3) * rand(2,3); }", "x"), "{2, 3, 4, 6, 9}");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = rand(1,-1); }", "x"), "bot");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; }", "x"), "top");
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = 12 / 0; }", "x"), "bot");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = 12 / rand(-1,1); }", "x"), "{-12, 12}");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = rand(-3,3) % 2; }", "x"), "{-1, 0, 1}");
+    }
+
+    #[test]
+    fn test_relations() {
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; int y; x = rand(0,3); y = x; assert(x >= 2); }", "y"), "{2, 3}");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; int y; x = rand(0,3); y = rand(0,3); assert(x + y == 6); }", "y"), "{3}");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = rand(0,3); assert(x == rand(2,5)); }", "x"), "{2, 3}");
+    }
+
+    #[test]
+    fn test_too_many_states() {
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = rand(0,100000); }", "x"), "top");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; int y; x = rand(0,200); y = rand(0,200); }", "y"), "top");
+        assert_eq!(value_of::<ConcreteDomain>("{ int x; x = 9223372036854775807 + 1; }", "x"), "top");
     }
 }
-*/
\ No newline at end of file
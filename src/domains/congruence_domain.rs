@@ -0,0 +1,246 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use std::fmt;
+use crate::ast::*;
+use crate::libs::rational::gcd;
+
+use super::value_domain::ValueDomain;
+use super::non_relational_domain::NonRelational;
+
+/// inverse of `a` modulo `m`, for `a` and `m` coprime.
+fn inverse(a : i128, m : i128) -> i128 {
+    let (mut r0, mut r1) = (a.rem_euclid(m), m);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(m)
+}
+
+/// abstraction of a set of integers by a congruence `aZ + b` : the integers
+/// equal to `b` modulo `a`. `Cong(0, b)` is the constant `b`, `Cong(1, 0)` is
+/// any integer, and otherwise `Cong(a, b)` always satisfies 0 <= b < a.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Congruence {
+    Bottom,
+    Cong(i64, i64),
+}
+
+impl Congruence {
+    /// builds the congruence `aZ + b`, any integer when it cannot be represented.
+    fn new(a : i128, b : i128) -> Self {
+        let a = a.abs();
+        let b = if a == 0 { b } else { b.rem_euclid(a) };
+        match (i64::try_from(a), i64::try_from(b)) {
+            (Ok(a), Ok(b)) => Congruence::Cong(a, b),
+            _ => Congruence::top(),
+        }
+    }
+
+    /// modulus and remainder of a non-bottom congruence.
+    fn parts(&self) -> Option<(i128, i128)> {
+        match self {
+            Congruence::Bottom => None,
+            Congruence::Cong(a, b) => Some((*a as i128, *b as i128)),
+        }
+    }
+}
+
+impl fmt::Display for Congruence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Congruence::Bottom => write!(f, "bot"),
+            Congruence::Cong(0, b) => write!(f, "{}", b),
+            Congruence::Cong(1, _) => write!(f, "top"),
+            Congruence::Cong(a, b) => write!(f, "{}Z+{}", a, b),
+        }
+    }
+}
+
+impl ValueDomain for Congruence {
+    fn top() -> Self {
+        Congruence::Cong(1, 0)
+    }
+
+    fn bottom() -> Self {
+        Congruence::Bottom
+    }
+
+    fn constant(n : i64) -> Self {
+        Congruence::Cong(0, n)
+    }
+
+    fn rand(lower : &Self, upper : &Self) -> Self {
+        match (lower, upper) {
+            (Congruence::Bottom, _) | (_, Congruence::Bottom) => Congruence::Bottom,
+            (Congruence::Cong(0, a), Congruence::Cong(0, b)) if a > b => Congruence::Bottom,
+            (Congruence::Cong(0, a), Congruence::Cong(0, b)) if a == b => *lower,
+            _ => Congruence::top(),
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, Congruence::Bottom)
+    }
+
+    // aZ + b is included in cZ + d when c divides a and b is in cZ + d
+    fn subset(&self, rhs : &Self) -> bool {
+        match (self.parts(), rhs.parts()) {
+            (None, _) => true,
+            (_, None) => false,
+            (Some((a, b)), Some((c, d))) =>
+                if c == 0 { a == 0 && b == d } else { a % c == 0 && (b - d) % c == 0 },
+        }
+    }
+
+    fn join(&self, rhs : &Self) -> Self {
+        match (self.parts(), rhs.parts()) {
+            (None, _) => *rhs,
+            (_, None) => *self,
+            (Some((a, b)), Some((c, d))) => Congruence::new(gcd(gcd(a, c), b - d), b),
+        }
+    }
+
+    // chinese remainder theorem : the intersection is empty, or a congruence
+    // modulo the least common multiple, kept to one of the operands when too large
+    fn meet(&self, rhs : &Self) -> Self {
+        match (self.parts(), rhs.parts()) {
+            (None, _) | (_, None) => Congruence::Bottom,
+            (Some((0, b)), _) => if Congruence::constant(b as i64).subset(rhs) { *self } else { Congruence::Bottom },
+            (_, Some((0, d))) => if Congruence::constant(d as i64).subset(self) { *rhs } else { Congruence::Bottom },
+            (Some((a, b)), Some((c, d))) => {
+                let g = gcd(a, c);
+                if (d - b) % g != 0 {
+                    return Congruence::Bottom;
+                }
+                let l = a / g * c;
+                if l > i64::MAX as i128 {
+                    return if a >= c { *self } else { *rhs };
+                }
+                let t = ((d - b) / g).rem_euclid(c / g) * inverse(a / g, c / g) % (c / g);
+                Congruence::new(l, b + a * t)
+            },
+        }
+    }
+
+    // the descending chains may be infinite : only top is refined
+    fn narrow(&self, rhs : &Self) -> Self {
+        if *self == Congruence::top() { *rhs } else { *self }
+    }
+
+    fn unary(&self, op : IntUnaryOp) -> Self {
+        match (op, self.parts()) {
+            (IntUnaryOp::SubUnary, Some((a, b))) => Congruence::new(a, -b),
+            _ => *self,
+        }
+    }
+
+    // divisions and modulos are exact by constants dividing the modulus,
+    // and have no result when the divisor is zero
+    fn binary(&self, op : IntBinaryOp, rhs : &Self) -> Self {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), rhs.parts()) else {
+            return Congruence::Bottom;
+        };
+        match op {
+            IntBinaryOp::Add => Congruence::new(gcd(a, c), b + d),
+            IntBinaryOp::Sub => Congruence::new(gcd(a, c), b - d),
+            IntBinaryOp::Mul => Congruence::new(gcd(gcd(a * c, a * d), c * b), b * d),
+            IntBinaryOp::Div | IntBinaryOp::Mod if c == 0 && d == 0 => Congruence::Bottom,
+            IntBinaryOp::Div =>
+                if c != 0 {
+                    Congruence::top()
+                }
+                else if a == 0 {
+                    Congruence::new(0, b / d)
+                }
+                else if a % d == 0 && b % d == 0 {
+                    Congruence::new(a / d, b / d)
+                }
+                else {
+                    Congruence::top()
+                },
+            // x % y is equal to x modulo every common divisor of the values of y
+            IntBinaryOp::Mod =>
+                if a == 0 && c == 0 {
+                    Congruence::new(0, b % d)
+                }
+                else if c == 0 && a % d == 0 && b % d == 0 {
+                    Congruence::constant(0)
+                }
+                else {
+                    Congruence::new(gcd(gcd(a, c), d), b)
+                },
+        }
+    }
+
+    fn compare(&self, cmp : &CompareOp, rhs : &Self) -> (Self, Self) {
+        match (cmp, self, rhs) {
+            (_, Congruence::Bottom, _) | (_, _, Congruence::Bottom) =>
+                (Congruence::Bottom, Congruence::Bottom),
+            (CompareOp::EQ, _, _) => {
+                let m = self.meet(rhs);
+                (m, m)
+            },
+            (_, Congruence::Cong(0, a), Congruence::Cong(0, b)) => {
+                let holds =
+                    match cmp {
+                        CompareOp::EQ => a == b,
+                        CompareOp::NE => a != b,
+                        CompareOp::LT => a < b,
+                        CompareOp::LE => a <= b,
+                        CompareOp::GT => a > b,
+                        CompareOp::GE => a >= b,
+                    };
+                if holds { (*self, *rhs) } else { (Congruence::Bottom, Congruence::Bottom) }
+            },
+            _ => (*self, *rhs),
+        }
+    }
+}
+
+/// congruence analysis domain.
+pub type CongruenceDomain = NonRelational<Congruence>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::testing::value_of;
+
+    #[test]
+    fn test_lattice() {
+        let cong = |a : i128, b : i128| Congruence::new(a, b);
+        assert_eq!(cong(0, 2).join(&cong(0, 6)), cong(4, 2));
+        assert_eq!(cong(4, 2).join(&cong(6, 0)), cong(2, 0));
+        assert_eq!(cong(4, 1).meet(&cong(6, 3)), cong(12, 9));
+        assert_eq!(cong(4, 1).meet(&cong(6, 0)), Congruence::Bottom);
+        assert_eq!(cong(4, 1).meet(&cong(0, 9)), cong(0, 9));
+        assert!(cong(4, 2).subset(&cong(2, 0)));
+        assert!(!cong(2, 0).subset(&cong(4, 2)));
+        assert_eq!(cong(4, 3).to_string(), "4Z+3");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = rand(0,1) * 4 + 2; }", "x"), "4Z+2");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } }", "x"), "4Z+2");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } x = 3 * x + 1; }", "x"), "12Z+7");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } x = x / 2; }", "x"), "2Z+1");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } x = x % 4; }", "x"), "4Z+2");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 4; if (rand(0,1) == 0) { x = 8; } x = x % 2; }", "x"), "0");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } x = x / 0; }", "x"), "bot");
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } assert(x == 10); }", "x"), "10");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 2; if (rand(0,1) == 0) { x = 6; } assert(x == 3); }", "x"), "bot");
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = 3; assert(x != 3); }", "x"), "bot");
+        // the remainders refine the dividend
+        assert_eq!(value_of::<CongruenceDomain>("{ int x; x = rand(0,100); assert(x % 4 == 1); }", "x"), "4Z+1");
+    }
+}
@@ -2,4 +2,199 @@
  * author : Narcisse.
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
- */
\ No newline at end of file
+ */
+use std::fmt;
+use crate::ast::*;
+
+use super::value_domain::ValueDomain;
+use super::non_relational_domain::NonRelational;
+
+/// abstraction of a set of integers by the constant lattice :
+/// the empty set, a single integer, or any integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constant {
+    Bottom,
+    Cst(i64),
+    Top,
+}
+
+impl Constant {
+    /// whether the comparison `self cmp rhs` may hold.
+    fn may_satisfy(&self, cmp : &CompareOp, rhs : &Self) -> bool {
+        match (self, rhs) {
+            (Constant::Bottom, _) | (_, Constant::Bottom) => false,
+            (Constant::Cst(a), Constant::Cst(b)) =>
+                match cmp {
+                    CompareOp::EQ => a == b,
+                    CompareOp::NE => a != b,
+                    CompareOp::LT => a < b,
+                    CompareOp::LE => a <= b,
+                    CompareOp::GT => a > b,
+                    CompareOp::GE => a >= b,
+                },
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Bottom => write!(f, "bot"),
+            Constant::Cst(a) => write!(f, "{}", a),
+            Constant::Top => write!(f, "top"),
+        }
+    }
+}
+
+impl ValueDomain for Constant {
+    fn top() -> Self {
+        Constant::Top
+    }
+
+    fn bottom() -> Self {
+        Constant::Bottom
+    }
+
+    fn constant(n : i64) -> Self {
+        Constant::Cst(n)
+    }
+
+    fn rand(lower : &Self, upper : &Self) -> Self {
+        match (lower, upper) {
+            (Constant::Bottom, _) | (_, Constant::Bottom) => Constant::Bottom,
+            (Constant::Cst(a), Constant::Cst(b)) if a > b => Constant::Bottom,
+            (Constant::Cst(a), Constant::Cst(b)) if a == b => Constant::Cst(*a),
+            _ => Constant::Top,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, Constant::Bottom)
+    }
+
+    fn subset(&self, rhs : &Self) -> bool {
+        match (self, rhs) {
+            (Constant::Bottom, _) | (_, Constant::Top) => true,
+            (Constant::Cst(a), Constant::Cst(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn join(&self, rhs : &Self) -> Self {
+        match (self, rhs) {
+            (Constant::Bottom, x) | (x, Constant::Bottom) => *x,
+            (Constant::Cst(a), Constant::Cst(b)) if a == b => *self,
+            _ => Constant::Top,
+        }
+    }
+
+    fn meet(&self, rhs : &Self) -> Self {
+        match (self, rhs) {
+            (Constant::Top, x) | (x, Constant::Top) => *x,
+            (Constant::Cst(a), Constant::Cst(b)) if a == b => *self,
+            _ => Constant::Bottom,
+        }
+    }
+
+    fn unary(&self, op : IntUnaryOp) -> Self {
+        match (op, self) {
+            (IntUnaryOp::SubUnary, Constant::Cst(a)) =>
+                a.checked_neg().map_or(Constant::Top, Constant::Cst),
+            (_, x) => *x,
+        }
+    }
+
+    // divisions and modulos by zero have no result, overflows give top
+    fn binary(&self, op : IntBinaryOp, rhs : &Self) -> Self {
+        let checked =
+            match op {
+                IntBinaryOp::Add => i64::checked_add,
+                IntBinaryOp::Sub => i64::checked_sub,
+                IntBinaryOp::Mul => i64::checked_mul,
+                IntBinaryOp::Div => i64::checked_div,
+                IntBinaryOp::Mod => i64::checked_rem,
+            };
+        match (op, self, rhs) {
+            (_, Constant::Bottom, _) | (_, _, Constant::Bottom) => Constant::Bottom,
+            (IntBinaryOp::Div | IntBinaryOp::Mod, _, Constant::Cst(0)) => Constant::Bottom,
+            (_, Constant::Cst(a), Constant::Cst(b)) =>
+                checked(*a, *b).map_or(Constant::Top, Constant::Cst),
+            (IntBinaryOp::Mul, Constant::Cst(0), _)
+            | (IntBinaryOp::Mul, _, Constant::Cst(0))
+            | (IntBinaryOp::Div | IntBinaryOp::Mod, Constant::Cst(0), _) => Constant::Cst(0),
+            (IntBinaryOp::Mod, _, Constant::Cst(1) | Constant::Cst(-1)) => Constant::Cst(0),
+            _ => Constant::Top,
+        }
+    }
+
+    fn compare(&self, cmp : &CompareOp, rhs : &Self) -> (Self, Self) {
+        if !self.may_satisfy(cmp, rhs) {
+            (Constant::Bottom, Constant::Bottom)
+        }
+        else if let CompareOp::EQ = cmp {
+            (self.meet(rhs), self.meet(rhs))
+        }
+        else {
+            (*self, *rhs)
+        }
+    }
+}
+
+/// constant propagation domain.
+pub type ConstantDomain = NonRelational<Constant>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::testing::value_of;
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 12; }", "x"), "12");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = -9; }", "x"), "-9");
+        // a declared variable may have any value
+        assert_eq!(value_of::<ConstantDomain>("{ int x; }", "x"), "top");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(value_of::<ConstantDomain>("{ int x; int y; x = 4; y = 5; x = x + y + 2; }", "x"), "11");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 49 - 1; }", "x"), "48");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 15 / 3; }", "x"), "5");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 17 % 5; }", "x"), "2");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = -7 / 2; }", "x"), "-3");
+    }
+
+    #[test]
+    fn test_rand() {
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(1,10); }", "x"), "top");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(3,3); }", "x"), "3");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(1,-1); }", "x"), "bot");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(-1,1) * 0; }", "x"), "0");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(1,5) + rand(2,3); }", "x"), "top");
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 12 / 0; }", "x"), "bot");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 12 % 0; }", "x"), "bot");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 12 / rand(-1,1); }", "x"), "top");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 0 / rand(-1,1); }", "x"), "0");
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 9223372036854775807 + 1; }", "x"), "top");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 99999999999999999999; }", "x"), "top");
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(0,5); assert(x == 3); }", "x"), "3");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(0,5); assert(3 == x); }", "x"), "3");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 2; assert(x > 3); }", "x"), "bot");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = 2; assert(x != 2); }", "x"), "bot");
+        assert_eq!(value_of::<ConstantDomain>("{ int x; x = rand(0,5); assert(x != 2); }", "x"), "top");
+    }
+}
@@ -2,4 +2,277 @@
  * author : Narcisse.
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
- */
\ No newline at end of file
+ */
+use crate::ast::*;
+use crate::symbol::*;
+use crate::interpreter::interpreter::{AnalysisError, AnalysisOptions};
+
+use super::domain::AbstractDomain;
+
+/// default bound on the number of disjuncts.
+pub const MAX_DISJUNCTS : usize = 8;
+
+/// disjunctive completion of a domain : a union of a bounded number of
+/// non-bottom elements of `D`. Branches of `if`s are kept apart by the joins,
+/// and `!=` comparisons are split into `<` and `>`; whenever there are more
+/// disjuncts than the bound, the closest ones are joined.
+#[derive(Clone)]
+pub struct Disjunctive<D : AbstractDomain> {
+    disjuncts : Vec<D>,
+    /// bound on the number of disjuncts, given by the options of the analysis
+    /// to its initial state and kept by the operations, `MAX_DISJUNCTS` otherwise.
+    bound : Option<usize>,
+}
+
+impl<D> PartialEq for Disjunctive<D>
+where D : AbstractDomain {
+    fn eq(&self, rhs : &Self) -> bool {
+        self.disjuncts == rhs.disjuncts
+    }
+}
+
+impl<D> Eq for Disjunctive<D>
+where D : AbstractDomain {}
+
+impl<D> Disjunctive<D>
+where D : AbstractDomain {
+    /// builds a disjunction, dropping empty and redundant disjuncts
+    /// and joining disjuncts until at most `bound` of them remain.
+    fn of_disjuncts(disjuncts : Vec<D>, bound : Option<usize>) -> Self {
+        let mut res : Vec<D> = Vec::new();
+        for d in disjuncts.into_iter().filter(|d| !d.is_bottom()) {
+            if res.iter().any(|r| d.subset(r)) {
+                continue;
+            }
+            res.retain(|r| !r.subset(&d));
+            res.push(d);
+        }
+        while res.len() > bound.unwrap_or(MAX_DISJUNCTS).max(1) {
+            // joining overlapping disjuncts loses less information
+            let (i, j) = (0..res.len())
+                .flat_map(|i| (i + 1..res.len()).map(move |j| (i, j)))
+                .find(|(i, j)| !res[*i].clone().meet(res[*j].clone()).is_bottom())
+                .unwrap_or((res.len() - 2, res.len() - 1));
+            let dj = res.remove(j);
+            res[i].join_with(dj);
+        }
+        Disjunctive { disjuncts : res, bound }
+    }
+
+    /// applies `f` to every disjunct.
+    fn map<F : FnMut(&mut D) -> D>(&mut self, f : F) -> Self {
+        Self::of_disjuncts(self.disjuncts.iter_mut().map(f).collect(), self.bound)
+    }
+
+    /// each new disjunct is paired with an old one it overlaps (or the one at
+    /// the same position), and every old disjunct is widened by `widen` with the
+    /// join of the disjuncts paired with it : the number of disjuncts cannot grow,
+    /// and each of them follows a widening sequence of `D`.
+    fn widen_by<F : FnMut(&mut D, D)>(&mut self, rhs : Self, mut widen : F) {
+        if self.disjuncts.is_empty() {
+            *self = Disjunctive { bound : self.bound.or(rhs.bound), ..rhs };
+            return;
+        }
+        let n = self.disjuncts.len();
+        let mut paired : Vec<D> = vec![D::bottom(); n];
+        for (j, r) in rhs.disjuncts.into_iter().enumerate() {
+            let i = self.disjuncts
+                .iter()
+                .position(|d| !d.clone().meet(r.clone()).is_bottom())
+                .unwrap_or(j.min(n - 1));
+            paired[i].join_with(r);
+        }
+        for (d, r) in self.disjuncts.iter_mut().zip(paired) {
+            let joined = d.clone().join(r);
+            widen(d, joined);
+        }
+    }
+
+    /// the disjuncts of the abstract element.
+    pub fn disjuncts(&self) -> &[D] {
+        &self.disjuncts
+    }
+}
+
+impl<D> AbstractDomain for Disjunctive<D>
+where D : AbstractDomain {
+    fn top() -> Self {
+        Disjunctive { disjuncts : vec![D::top()], bound : None }
+    }
+
+    fn initial_state(options : &AnalysisOptions) -> Self {
+        Disjunctive { disjuncts : vec![D::initial_state(options)], bound : Some(options.disjuncts) }
+    }
+
+    fn bottom() -> Self {
+        Disjunctive { disjuncts : Vec::new(), bound : None }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.disjuncts.is_empty()
+    }
+
+    // sound but incomplete : each disjunct has to fit in a single disjunct of rhs
+    fn subset(&self, rhs : &Self) -> bool {
+        self.disjuncts
+            .iter()
+            .all(|d| rhs.disjuncts.iter().any(|r| d.subset(r)))
+    }
+
+    fn join_with(&mut self, rhs : Self) {
+        let mut disjuncts = std::mem::take(&mut self.disjuncts);
+        disjuncts.extend(rhs.disjuncts);
+        *self = Self::of_disjuncts(disjuncts, self.bound.or(rhs.bound));
+    }
+
+    fn meet_with(&mut self, rhs : Self) {
+        let disjuncts =
+            self.disjuncts
+                .iter()
+                .flat_map(|d| rhs.disjuncts.iter().map(|r| d.clone().meet(r.clone())))
+                .collect();
+        *self = Self::of_disjuncts(disjuncts, self.bound.or(rhs.bound));
+    }
+
+    fn widen_with(&mut self, rhs : Self) {
+        self.widen_by(rhs, |d, r| d.widen_with(r))
+    }
+
+    fn widen_thresholds_with(&mut self, rhs : Self, thresholds : &[i64]) {
+        self.widen_by(rhs, |d, r| d.widen_thresholds_with(r, thresholds))
+    }
+
+    // each old disjunct is narrowed by the join of the new disjuncts it overlaps,
+    // and joined back with them so that none of them is lost; old disjuncts
+    // overlapping no new one are dropped. When some new disjunct overlaps no
+    // old one, the narrowing falls back to `rhs`.
+    fn narrow_with(&mut self, rhs : Self) {
+        let n = self.disjuncts.len();
+        let mut paired : Vec<D> = vec![D::bottom(); n];
+        for r in rhs.disjuncts.iter() {
+            match self.disjuncts.iter().position(|d| !d.clone().meet(r.clone()).is_bottom()) {
+                Some(i) => paired[i].join_with(r.clone()),
+                None => {
+                    *self = Disjunctive { bound : self.bound.or(rhs.bound), ..rhs };
+                    return;
+                }
+            }
+        }
+        let disjuncts =
+            self.disjuncts
+                .iter()
+                .zip(paired)
+                .filter(|(_, r)| !r.is_bottom())
+                .map(|(d, r)| d.clone().narrow(r.clone()).join(r))
+                .collect();
+        *self = Self::of_disjuncts(disjuncts, self.bound.or(rhs.bound));
+    }
+
+    fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self {
+        let disjuncts =
+            self.disjuncts
+                .iter_mut()
+                .flat_map(|d| match cmp {
+                    CompareOp::NE =>
+                        vec![d.compare(e1, &CompareOp::LT, e2), d.compare(e1, &CompareOp::GT, e2)],
+                    _ => vec![d.compare(e1, cmp, e2)],
+                })
+                .collect();
+        Self::of_disjuncts(disjuncts, self.bound)
+    }
+
+    fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError> {
+        let disjuncts =
+            self.disjuncts
+                .iter_mut()
+                .map(|d| d.assign(v, e))
+                .collect::<Result<Vec<D>, AnalysisError>>()?;
+        Ok(Self::of_disjuncts(disjuncts, self.bound))
+    }
+
+    fn add_variable(&mut self, v : &Symbol) -> Self {
+        self.map(|d| d.add_variable(v))
+    }
+
+    fn remove_variable(&mut self, v : &Symbol) -> Self {
+        self.map(|d| d.remove_variable(v))
+    }
+
+    fn forget(&mut self, v : &Symbol) -> Self {
+        self.map(|d| d.forget(v))
+    }
+
+    fn print(&mut self, symbol : Symbol) -> String {
+        if self.disjuncts.is_empty() {
+            return D::bottom().print(symbol);
+        }
+        self.disjuncts
+            .iter_mut()
+            .map(|d| d.print(symbol.clone()))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::interval_domain::IntervalDomain;
+    use crate::domains::testing::{run, step, value_of};
+
+    #[test]
+    fn test_split_at_if() {
+        let src = "{ int x; int y;
+            if (rand(0,1) == 0) { x = 1; y = 1; } else { x = 3; y = 3; }
+            assert(x == y); }";
+        assert_eq!(value_of::<Disjunctive<IntervalDomain>>(src, "y"), "[1;1] | [3;3]");
+        assert_eq!(value_of::<IntervalDomain>(src, "y"), "[1;3]");
+    }
+
+    #[test]
+    fn test_split_at_compare() {
+        let src = "{ int x; x = rand(-5,5); assert(x != 0); }";
+        assert_eq!(value_of::<Disjunctive<IntervalDomain>>(src, "x"), "[-5;-1] | [1;5]");
+    }
+
+    #[test]
+    fn test_bound() {
+        let src = "{ int x; x = rand(-5,5);
+            if (x < 0) { x = -10; } else { if (x == 0) { x = 0; } else { x = 10; } } }";
+        assert_eq!(value_of::<Disjunctive<IntervalDomain>>(src, "x"), "[-10;-10] | [0;0] | [10;10]");
+        // the bound of the initial state is kept along the program
+        let (_, symbols) = run::<Disjunctive<IntervalDomain>>(src);
+        let options = AnalysisOptions { disjuncts : 2, ..AnalysisOptions::default() };
+        let state = Disjunctive::<IntervalDomain>::initial_state(&options).add_variable(&symbols["x"]);
+        assert_eq!(step(state, src).print(symbols["x"].clone()), "[-10;-10] | [0;10]");
+    }
+
+    #[test]
+    fn test_widening_terminates() {
+        let (mut state, symbols) = run::<Disjunctive<IntervalDomain>>(
+            "{ int x; x = rand(0,1); if (x == 0) { x = -1; } else { x = 1; } }");
+        let x = &symbols["x"];
+        let one = IntExpr::Const { span : lrpar::Span::new(0, 0), cst : "1".to_string() };
+        let mut iterations = 0;
+        loop {
+            let mut next = state.clone();
+            for d in next.disjuncts.iter_mut() {
+                let incr = IntExpr::Binary {
+                    span : lrpar::Span::new(0, 0),
+                    op : IntBinaryOp::Add,
+                    lhs : Box::new(IntExpr::Ident { span : lrpar::Span::new(0, 0), var : x.clone() }),
+                    rhs : Box::new(one.clone()),
+                };
+                *d = d.assign(x, &incr).unwrap();
+            }
+            let widened = state.clone().widen(next);
+            iterations += 1;
+            if widened.subset(&state) {
+                break;
+            }
+            state = widened;
+        }
+        assert!(iterations <= 3);
+        assert_eq!(state.disjuncts().len(), 2);
+    }
+}
@@ -7,10 +7,25 @@
 /// You need to precise top, bottom, and basic operators
 /// in order for the interpreter to manipulate it.
 use crate::ast::*;
-use crate::interpreter::interpreter::AnalysisError;
+use crate::interpreter::interpreter::{AnalysisError, AnalysisOptions};
 use crate::symbol::*;
 
+/// initialization status of a variable over the executions reaching a point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Init {
+    Initialized,
+    MaybeUninitialized,
+    Uninitialized,
+}
+
 pub trait AbstractDomain : Clone + Eq {
+    /// set of all the environments, used as initial state
+    fn top() -> Self;
+    /// initial state of an analysis with the parameters `options`,
+    /// for the domains configured by them
+    fn initial_state(_options : &AnalysisOptions) -> Self {
+        Self::top()
+    }
     /// empty set
     fn bottom() -> Self;
 
@@ -37,6 +52,13 @@ pub trait AbstractDomain : Clone + Eq {
         self
     }
 
+    /// widening with thresholds : the unstable bounds stop at the next value of
+    /// the sorted `thresholds`. Defaults to the widening for the domains without bounds
+    fn widen_thresholds(mut self, rhs : Self, thresholds : &[i64]) -> Self {
+        self.widen_thresholds_with(rhs, thresholds);
+        self
+    }
+
     /// narrowing : gain precision without loosing soundness
     fn narrow(mut self, rhs : Self) -> Self {
         self.narrow_with(rhs);
@@ -49,6 +71,10 @@ pub trait AbstractDomain : Clone + Eq {
     fn meet_with(&mut self, rhs : Self);
     /// helper function for the widening operator
     fn widen_with(&mut self, rhs : Self);
+    /// helper function for the widening operator with thresholds
+    fn widen_thresholds_with(&mut self, rhs : Self, _thresholds : &[i64]) {
+        self.widen_with(rhs)
+    }
     /// helper function for the narrowing operator
     fn narrow_with(&mut self, rhs : Self);
 
@@ -56,10 +82,17 @@ pub trait AbstractDomain : Clone + Eq {
     fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self;
     /// helper function to represent the assignment
     fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError>;
-    /// helper function to add variable from scope
+    /// helper function to add variable from scope, with any value
     fn add_variable(&mut self, v : &Symbol) -> Self;
     /// helper function to remove variable from scope
     fn remove_variable(&mut self, v : &Symbol) -> Self;
+    /// helper function to give any value to a variable
+    fn forget(&mut self, v : &Symbol) -> Self;
+
+    /// initialization status of a variable, for the domains tracking it
+    fn initialization(&self, _v : &Symbol) -> Init {
+        Init::Initialized
+    }
 
     /// pretty printer
     fn print(&mut self, symbol : Symbol) -> String;
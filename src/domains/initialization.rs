@@ -0,0 +1,215 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ast::*;
+use crate::symbol::*;
+use crate::interpreter::interpreter::{AnalysisError, AnalysisOptions};
+use crate::libs::map::{Map, MapTrait};
+
+use super::domain::{AbstractDomain, Init};
+
+impl Init {
+    fn join(self, rhs : Self) -> Self {
+        if self == rhs { self } else { Init::MaybeUninitialized }
+    }
+
+    fn subset(self, rhs : Self) -> bool {
+        self == rhs || rhs == Init::MaybeUninitialized
+    }
+}
+
+/// product of a domain with the initialization status of the variables : a
+/// declared variable is uninitialized until it is assigned. The variables which
+/// may be uninitialized may have any value in `D`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Initialization<D : AbstractDomain> {
+    values : D,
+    status : Map<Init>,
+}
+
+impl<D> Initialization<D>
+where D : AbstractDomain {
+    /// pointwise application of a binary operator on the statuses.
+    fn pointwise<F : FnMut(&Init, &Init) -> Init>(&mut self, rhs : &Self, f : F) {
+        self.status.map2z(&rhs.status, f)
+            .expect("operands should be defined over the same variables");
+    }
+
+    /// forgets the values of the variables which are uninitialized in `self`
+    /// but not in `rhs` : once joined, they may have any value.
+    fn forget_uninitialized(&mut self, rhs : &Self) {
+        let mut vars = Vec::new();
+        self.status.iter2z(&rhs.status, |v, a, _| {
+            if *a == Init::Uninitialized {
+                vars.push(v.clone());
+            }
+        }).expect("operands should be defined over the same variables");
+        for v in vars {
+            self.values = self.values.forget(&v);
+        }
+    }
+
+    /// keeps the statuses of `self` with the values `values`.
+    fn with_values(&self, values : D) -> Self {
+        if values.is_bottom() {
+            Self::bottom()
+        }
+        else {
+            Initialization { values, status : self.status.clone() }
+        }
+    }
+
+    /// the element of `D` over-approximating the values of the variables.
+    pub fn values(&self) -> &D {
+        &self.values
+    }
+}
+
+impl<D> AbstractDomain for Initialization<D>
+where D : AbstractDomain {
+    fn top() -> Self {
+        Initialization { values : D::top(), status : Map::new() }
+    }
+
+    fn initial_state(options : &AnalysisOptions) -> Self {
+        Initialization { values : D::initial_state(options), status : Map::new() }
+    }
+
+    fn bottom() -> Self {
+        Initialization { values : D::bottom(), status : Map::new() }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.values.is_bottom()
+    }
+
+    fn subset(&self, rhs : &Self) -> bool {
+        self.is_bottom()
+            || (!rhs.is_bottom()
+                && self.values.subset(&rhs.values)
+                && self.status.clone()
+                    .for_all2z(&rhs.status, |_, a, b| a.subset(*b))
+                    .expect("operands should be defined over the same variables"))
+    }
+
+    fn join_with(&mut self, mut rhs : Self) {
+        if self.is_bottom() {
+            *self = rhs;
+        }
+        else if !rhs.is_bottom() {
+            self.forget_uninitialized(&rhs);
+            rhs.forget_uninitialized(self);
+            self.pointwise(&rhs, |a, b| a.join(*b));
+            self.values.join_with(rhs.values);
+        }
+    }
+
+    // the operands of a meet refine the same state : a variable initialized
+    // in one and uninitialized in the other cannot be read anyway
+    fn meet_with(&mut self, rhs : Self) {
+        if rhs.is_bottom() {
+            *self = Self::bottom();
+        }
+        else if !self.is_bottom() {
+            let values = self.values.clone().meet(rhs.values);
+            *self = self.with_values(values);
+        }
+    }
+
+    // the statuses have a finite height, they are joined
+    fn widen_with(&mut self, mut rhs : Self) {
+        if self.is_bottom() {
+            *self = rhs;
+        }
+        else if !rhs.is_bottom() {
+            self.forget_uninitialized(&rhs);
+            rhs.forget_uninitialized(self);
+            self.pointwise(&rhs, |a, b| a.join(*b));
+            self.values.widen_with(rhs.values);
+        }
+    }
+
+    fn widen_thresholds_with(&mut self, mut rhs : Self, thresholds : &[i64]) {
+        if self.is_bottom() {
+            *self = rhs;
+        }
+        else if !rhs.is_bottom() {
+            self.forget_uninitialized(&rhs);
+            rhs.forget_uninitialized(self);
+            self.pointwise(&rhs, |a, b| a.join(*b));
+            self.values.widen_thresholds_with(rhs.values, thresholds);
+        }
+    }
+
+    fn narrow_with(&mut self, rhs : Self) {
+        if rhs.is_bottom() {
+            *self = Self::bottom();
+        }
+        else if !self.is_bottom() {
+            let values = self.values.clone().narrow(rhs.values);
+            *self = self.with_values(values);
+        }
+    }
+
+    fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self {
+        let values = self.values.compare(e1, cmp, e2);
+        self.with_values(values)
+    }
+
+    fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError> {
+        let values = self.values.assign(v, e)?;
+        let mut res = self.with_values(values);
+        if !res.is_bottom() {
+            res.status.add(v, &Init::Initialized).map_err(|_| AnalysisError::UnknownVariable)?;
+        }
+        Ok(res)
+    }
+
+    fn add_variable(&mut self, v : &Symbol) -> Self {
+        let values = self.values.add_variable(v);
+        let mut res = self.with_values(values);
+        if !res.is_bottom() {
+            res.status.add(v, &Init::Uninitialized).expect("persistent maps always accept bindings");
+        }
+        res
+    }
+
+    fn remove_variable(&mut self, v : &Symbol) -> Self {
+        let values = self.values.remove_variable(v);
+        let mut res = self.with_values(values);
+        res.status.remove(v);
+        res
+    }
+
+    fn forget(&mut self, v : &Symbol) -> Self {
+        let values = self.values.forget(v);
+        self.with_values(values)
+    }
+
+    // the variables that were not declared are considered initialized
+    fn initialization(&self, v : &Symbol) -> Init {
+        self.status.find(v).copied().unwrap_or(Init::Initialized)
+    }
+
+    fn print(&mut self, symbol : Symbol) -> String {
+        self.values.print(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::interval_domain::IntervalDomain;
+    use crate::domains::testing::run;
+
+    #[test]
+    fn test_status() {
+        let src = "{ int x; int y; int z; x = 1; if (rand(0,1) == 0) { y = 2; } }";
+        let (state, symbols) = run::<Initialization<IntervalDomain>>(src);
+        assert_eq!(state.initialization(&symbols["x"]), Init::Initialized);
+        assert_eq!(state.initialization(&symbols["y"]), Init::MaybeUninitialized);
+        assert_eq!(state.initialization(&symbols["z"]), Init::Uninitialized);
+    }
+}
@@ -2,4 +2,417 @@
  * author : Narcisse.
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
- */
\ No newline at end of file
+ */
+use std::fmt;
+use std::cmp::{max, min};
+use crate::ast::*;
+
+use super::value_domain::ValueDomain;
+use super::non_relational_domain::NonRelational;
+
+/// bound of an interval : an integer or an infinity.
+/// The derived order is the natural one, -oo < n < +oo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bound {
+    MinusInf,
+    Finite(i64),
+    PlusInf,
+}
+
+impl Bound {
+    /// converts the exact result of an operation into a bound, rounding towards
+    /// -oo when `lower` holds and towards +oo otherwise in case of overflow.
+    pub fn of_i128(n : i128, lower : bool) -> Self {
+        match i64::try_from(n) {
+            Ok(n) => Bound::Finite(n),
+            Err(_) if n > 0 =>
+                if lower { Bound::Finite(i64::MAX) } else { Bound::PlusInf },
+            Err(_) =>
+                if lower { Bound::MinusInf } else { Bound::Finite(i64::MIN) },
+        }
+    }
+
+    fn sign(&self) -> i32 {
+        match self {
+            Bound::MinusInf => -1,
+            Bound::Finite(n) => n.signum() as i32,
+            Bound::PlusInf => 1,
+        }
+    }
+
+    fn infinity(sign : i32) -> Self {
+        if sign < 0 { Bound::MinusInf } else { Bound::PlusInf }
+    }
+
+    /// opposite of a bound, rounded like `of_i128`.
+    pub fn opp(self, lower : bool) -> Self {
+        match self {
+            Bound::MinusInf => Bound::PlusInf,
+            Bound::Finite(n) => Bound::of_i128(-(n as i128), lower),
+            Bound::PlusInf => Bound::MinusInf,
+        }
+    }
+
+    /// sum of two bounds, -oo + +oo never happens on well-formed intervals.
+    pub fn add(self, rhs : Self, lower : bool) -> Self {
+        match (self, rhs) {
+            (Bound::Finite(a), Bound::Finite(b)) => Bound::of_i128(a as i128 + b as i128, lower),
+            (Bound::Finite(_), inf) | (inf, Bound::Finite(_)) => inf,
+            (a, _) => a,
+        }
+    }
+
+    /// product of two bounds, with 0 * oo = 0.
+    pub fn mul(self, rhs : Self, lower : bool) -> Self {
+        match (self, rhs) {
+            (Bound::Finite(a), Bound::Finite(b)) => Bound::of_i128(a as i128 * b as i128, lower),
+            (a, b) if a.sign() == 0 || b.sign() == 0 => Bound::Finite(0),
+            (a, b) => Bound::infinity(a.sign() * b.sign()),
+        }
+    }
+
+    /// truncated division of two bounds, `rhs` is never 0.
+    /// Finite values divided by an infinity give 0, as do two infinities :
+    /// the other corners of the operands always account for the infinite quotients.
+    pub fn div(self, rhs : Self, lower : bool) -> Self {
+        match (self, rhs) {
+            (Bound::Finite(a), Bound::Finite(b)) => Bound::of_i128(a as i128 / b as i128, lower),
+            (a, Bound::Finite(b)) => Bound::infinity(a.sign() * b.signum() as i32),
+            (_, _) => Bound::Finite(0),
+        }
+    }
+
+    pub fn pred(self) -> Self {
+        self.add(Bound::Finite(-1), true)
+    }
+
+    pub fn succ(self) -> Self {
+        self.add(Bound::Finite(1), false)
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bound::MinusInf => write!(f, "-inf"),
+            Bound::Finite(n) => write!(f, "{}", n),
+            Bound::PlusInf => write!(f, "+inf"),
+        }
+    }
+}
+
+/// abstraction of a set of integers by its smallest enclosing interval.
+/// `Itv(a, b)` always satisfies a <= b, a < +oo and b > -oo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Bottom,
+    Itv(Bound, Bound),
+}
+
+impl Interval {
+    pub fn singleton(n : i64) -> Self {
+        Interval::Itv(Bound::Finite(n), Bound::Finite(n))
+    }
+
+    /// builds the interval [a, b], empty if a > b.
+    pub fn new(a : Bound, b : Bound) -> Self {
+        if a > b || a == Bound::PlusInf || b == Bound::MinusInf {
+            Interval::Bottom
+        }
+        else {
+            Interval::Itv(a, b)
+        }
+    }
+
+    pub fn contains(&self, n : i64) -> bool {
+        match self {
+            Interval::Bottom => false,
+            Interval::Itv(a, b) => *a <= Bound::Finite(n) && Bound::Finite(n) <= *b,
+        }
+    }
+
+    /// smallest interval containing the results of `f` on the four corners.
+    fn corners<F : Fn(Bound, Bound, bool) -> Bound>(self, rhs : Self, f : F) -> Self {
+        match (self, rhs) {
+            (Interval::Itv(a, b), Interval::Itv(c, d)) => {
+                let lows = [f(a, c, true), f(a, d, true), f(b, c, true), f(b, d, true)];
+                let highs = [f(a, c, false), f(a, d, false), f(b, c, false), f(b, d, false)];
+                Interval::new(
+                    *lows.iter().min().unwrap(),
+                    *highs.iter().max().unwrap()
+                )
+            },
+            _ => Interval::Bottom,
+        }
+    }
+
+    /// parts of the interval that are strictly positive and strictly negative.
+    fn sign_split(self) -> (Self, Self) {
+        (
+            self.meet(&Interval::new(Bound::Finite(1), Bound::PlusInf)),
+            self.meet(&Interval::new(Bound::MinusInf, Bound::Finite(-1)))
+        )
+    }
+
+    /// truncated division, restricted to non-zero divisors.
+    fn div(self, rhs : Self) -> Self {
+        let (pos, neg) = rhs.sign_split();
+        self.corners(pos, Bound::div).join(&self.corners(neg, Bound::div))
+    }
+
+    /// remainder of the truncated division, restricted to non-zero divisors :
+    /// its sign is the one of the dividend and it is smaller than the divisor in absolute value.
+    fn rem(self, rhs : Self) -> Self {
+        let (pos, neg) = rhs.sign_split();
+        let divisor = pos.join(&neg.unary(IntUnaryOp::SubUnary));
+        match (self, divisor) {
+            (Interval::Itv(Bound::Finite(a), Bound::Finite(b)), Interval::Itv(Bound::Finite(c), Bound::Finite(d)))
+                if a == b && c == d => Interval::singleton(a % c),
+            (Interval::Itv(a, b), Interval::Itv(_, m)) => {
+                let m = m.pred();
+                Interval::new(
+                    if a.sign() >= 0 { Bound::Finite(0) } else { max(a, m.opp(true)) },
+                    if b.sign() <= 0 { Bound::Finite(0) } else { min(b, m) }
+                )
+            },
+            _ => Interval::Bottom,
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interval::Bottom => write!(f, "bot"),
+            Interval::Itv(a, b) => write!(f, "[{};{}]", a, b),
+        }
+    }
+}
+
+impl ValueDomain for Interval {
+    fn top() -> Self {
+        Interval::Itv(Bound::MinusInf, Bound::PlusInf)
+    }
+
+    fn bottom() -> Self {
+        Interval::Bottom
+    }
+
+    fn constant(n : i64) -> Self {
+        Interval::singleton(n)
+    }
+
+    /// abstraction of `rand(l, u)` where l ranges in `lower` and u in `upper`.
+    fn rand(lower : &Self, upper : &Self) -> Self {
+        match (lower, upper) {
+            (Interval::Itv(a, _), Interval::Itv(_, d)) => Interval::new(*a, *d),
+            _ => Interval::Bottom,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, Interval::Bottom)
+    }
+
+    fn subset(&self, rhs : &Self) -> bool {
+        match (self, rhs) {
+            (Interval::Bottom, _) => true,
+            (_, Interval::Bottom) => false,
+            (Interval::Itv(a, b), Interval::Itv(c, d)) => c <= a && b <= d,
+        }
+    }
+
+    fn join(&self, rhs : &Self) -> Self {
+        match (*self, *rhs) {
+            (Interval::Bottom, x) | (x, Interval::Bottom) => x,
+            (Interval::Itv(a, b), Interval::Itv(c, d)) => Interval::Itv(min(a, c), max(b, d)),
+        }
+    }
+
+    fn meet(&self, rhs : &Self) -> Self {
+        match (*self, *rhs) {
+            (Interval::Bottom, _) | (_, Interval::Bottom) => Interval::Bottom,
+            (Interval::Itv(a, b), Interval::Itv(c, d)) => Interval::new(max(a, c), min(b, d)),
+        }
+    }
+
+    /// standard widening : unstable bounds jump to infinity.
+    fn widen(&self, rhs : &Self) -> Self {
+        match (*self, *rhs) {
+            (Interval::Bottom, x) | (x, Interval::Bottom) => x,
+            (Interval::Itv(a, b), Interval::Itv(c, d)) =>
+                Interval::Itv(
+                    if c < a { Bound::MinusInf } else { a },
+                    if d > b { Bound::PlusInf } else { b }
+                ),
+        }
+    }
+
+    /// unstable bounds jump to the nearest threshold beyond them, or to infinity.
+    fn widen_thresholds(&self, rhs : &Self, thresholds : &[i64]) -> Self {
+        match (*self, *rhs) {
+            (Interval::Bottom, x) | (x, Interval::Bottom) => x,
+            (Interval::Itv(a, b), Interval::Itv(c, d)) =>
+                Interval::Itv(
+                    if c < a {
+                        thresholds.iter().rev().map(|t| Bound::Finite(*t)).find(|t| *t <= c).unwrap_or(Bound::MinusInf)
+                    }
+                    else { a },
+                    if d > b {
+                        thresholds.iter().map(|t| Bound::Finite(*t)).find(|t| *t >= d).unwrap_or(Bound::PlusInf)
+                    }
+                    else { b }
+                ),
+        }
+    }
+
+    /// standard narrowing : only infinite bounds are refined.
+    fn narrow(&self, rhs : &Self) -> Self {
+        match (*self, *rhs) {
+            (Interval::Bottom, _) | (_, Interval::Bottom) => Interval::Bottom,
+            (Interval::Itv(a, b), Interval::Itv(c, d)) =>
+                Interval::new(
+                    if a == Bound::MinusInf { c } else { a },
+                    if b == Bound::PlusInf { d } else { b }
+                ),
+        }
+    }
+
+    /// forward abstract semantics of the integer unary operators.
+    fn unary(&self, op : IntUnaryOp) -> Self {
+        match (op, *self) {
+            (_, Interval::Bottom) => Interval::Bottom,
+            (IntUnaryOp::AddUnary, x) => x,
+            (IntUnaryOp::SubUnary, Interval::Itv(a, b)) => Interval::Itv(b.opp(true), a.opp(false)),
+        }
+    }
+
+    /// forward abstract semantics of the integer binary operators :
+    /// divisions and modulos by zero have no result.
+    fn binary(&self, op : IntBinaryOp, rhs : &Self) -> Self {
+        match op {
+            IntBinaryOp::Add => self.corners(*rhs, |x, y, l| x.add(y, l)),
+            IntBinaryOp::Sub => self.corners(rhs.unary(IntUnaryOp::SubUnary), |x, y, l| x.add(y, l)),
+            IntBinaryOp::Mul => self.corners(*rhs, Bound::mul),
+            IntBinaryOp::Div => self.div(*rhs),
+            IntBinaryOp::Mod => self.rem(*rhs),
+        }
+    }
+
+    /// backward semantics of `self cmp rhs` : refines both operands to the values
+    /// that can satisfy the comparison.
+    fn compare(&self, cmp : &CompareOp, rhs : &Self) -> (Self, Self) {
+        let (Interval::Itv(a, b), Interval::Itv(c, d)) = (*self, *rhs) else {
+            return (Interval::Bottom, Interval::Bottom);
+        };
+        let (x, y) =
+            match cmp {
+                CompareOp::EQ => (self.meet(rhs), self.meet(rhs)),
+                CompareOp::NE => {
+                    if a == b && c == d && a == c {
+                        (Interval::Bottom, Interval::Bottom)
+                    }
+                    else if c == d {
+                        (Interval::new(if a == c { a.succ() } else { a },
+                            if b == c { b.pred() } else { b }), *rhs)
+                    }
+                    else if a == b {
+                        (*self, Interval::new(if c == a { c.succ() } else { c },
+                            if d == a { d.pred() } else { d }))
+                    }
+                    else {
+                        (*self, *rhs)
+                    }
+                },
+                CompareOp::LE =>
+                    (Interval::new(a, min(b, d)), Interval::new(max(a, c), d)),
+                CompareOp::LT =>
+                    (Interval::new(a, min(b, d.pred())), Interval::new(max(a.succ(), c), d)),
+                CompareOp::GE => {
+                    let (y, x) = rhs.compare(&CompareOp::LE, self);
+                    (x, y)
+                },
+                CompareOp::GT => {
+                    let (y, x) = rhs.compare(&CompareOp::LT, self);
+                    (x, y)
+                },
+            };
+        if x.is_bottom() || y.is_bottom() {
+            (Interval::Bottom, Interval::Bottom)
+        }
+        else {
+            (x, y)
+        }
+    }
+}
+
+/// interval domain.
+pub type IntervalDomain = NonRelational<Interval>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::testing::value_of;
+
+    fn itv(a : i64, b : i64) -> Interval {
+        Interval::new(Bound::Finite(a), Bound::Finite(b))
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(1,5) + rand(2,3); }", "x"), "[3;8]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = -rand(10,15); }", "x"), "[-15;-10]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(10,15) - rand(1,2); }", "x"), "[8;14]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(-1,1) * rand(10,100); }", "x"), "[-100;100]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; int y; int z; x = rand(1,3); y = rand(2,5); z = x + 2 * y; }", "z"), "[5;13]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(1,-1); }", "x"), "bot");
+    }
+
+    #[test]
+    fn test_division() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(10,100) / rand(2,5); }", "x"), "[2;50]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(-10,10) / rand(2,5); }", "x"), "[-5;5]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = 12 / 0; }", "x"), "bot");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = 12 / rand(-1,1); }", "x"), "[-12;12]");
+        assert_eq!(itv(-7, 7).binary(IntBinaryOp::Div, &Interval::new(Bound::Finite(1), Bound::PlusInf)), itv(-7, 7));
+    }
+
+    #[test]
+    fn test_modulo() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = 17 % 5; }", "x"), "[2;2]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = -17 % 5; }", "x"), "[-2;-2]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100) % rand(-3,3); }", "x"), "[0;2]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(-100,1) % 10; }", "x"), "[-9;1]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100) % 0; }", "x"), "bot");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let big = Interval::new(Bound::Finite(i64::MAX - 1), Bound::Finite(i64::MAX));
+        assert_eq!(big.binary(IntBinaryOp::Add, &itv(1, 1)),
+            Interval::new(Bound::Finite(i64::MAX), Bound::PlusInf));
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x < 10); }", "x"), "[0;9]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x >= 10); }", "x"), "[10;100]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(10 > x); }", "x"), "[0;9]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x != 0); }", "x"), "[1;100]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x == 42); }", "x"), "[42;42]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x > 100); }", "x"), "bot");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; int y; x = rand(0,10); y = rand(5,20); assert(x >= y); }", "y"), "[5;10]");
+    }
+
+    #[test]
+    fn test_widen_narrow() {
+        assert_eq!(itv(0, 1).widen(&itv(0, 2)), Interval::new(Bound::Finite(0), Bound::PlusInf));
+        assert_eq!(itv(0, 1).widen(&itv(-1, 1)), Interval::new(Bound::MinusInf, Bound::Finite(1)));
+        let thresholds = [-10, 0, 5, 100];
+        assert_eq!(itv(0, 1).widen_thresholds(&itv(0, 2), &thresholds), itv(0, 5));
+        assert_eq!(itv(0, 5).widen_thresholds(&itv(-1, 6), &thresholds), itv(-10, 100));
+        assert_eq!(itv(0, 100).widen_thresholds(&itv(0, 101), &thresholds), Interval::new(Bound::Finite(0), Bound::PlusInf));
+        assert_eq!(Interval::new(Bound::Finite(0), Bound::PlusInf).narrow(&itv(0, 10)), itv(0, 10));
+        assert_eq!(itv(0, 5).narrow(&itv(0, 10)), itv(0, 5));
+    }
+}
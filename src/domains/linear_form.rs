@@ -0,0 +1,134 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ast::*;
+use crate::symbol::*;
+use crate::interpreter::interpreter::AnalysisError;
+
+use super::value_domain::ValueDomain;
+use super::interval_domain::{Bound, Interval};
+
+/// environments of the relational domains, as seen by the linearization :
+/// variables are numbered, and can be bounded by an interval.
+pub trait Linearizable {
+    /// number of variables
+    fn dimension(&self) -> usize;
+    /// number of a variable
+    fn index(&self, v : &Symbol) -> Result<usize, AnalysisError>;
+    /// interval of the values of the variable of index k
+    fn bounds(&self, k : usize) -> Interval;
+}
+
+/// linear form `sum coeffs[k] * x(k) + cst`, where `cst` gathers the
+/// non-linear parts of an expression, evaluated in the interval domain.
+#[derive(Clone)]
+pub struct LinearForm {
+    pub coeffs : Vec<i64>,
+    pub cst : Interval,
+}
+
+impl LinearForm {
+    pub fn constant(n : usize, cst : Interval) -> Self {
+        LinearForm { coeffs : vec![0; n], cst }
+    }
+
+    /// the form `x(k)`.
+    pub fn variable(n : usize, k : usize) -> Self {
+        let mut f = LinearForm::constant(n, Interval::singleton(0));
+        f.coeffs[k] = 1;
+        f
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.coeffs.iter().all(|a| *a == 0)
+    }
+
+    /// indices of the variables appearing in the form.
+    pub fn vars(&self) -> Vec<usize> {
+        (0..self.coeffs.len()).filter(|k| self.coeffs[*k] != 0).collect()
+    }
+
+    pub fn scale(&self, k : i64) -> Option<Self> {
+        Some(LinearForm {
+            coeffs : self.coeffs.iter().map(|a| a.checked_mul(k)).collect::<Option<Vec<i64>>>()?,
+            cst : self.cst.binary(IntBinaryOp::Mul, &Interval::singleton(k)),
+        })
+    }
+
+    pub fn add(&self, rhs : &Self) -> Option<Self> {
+        Some(LinearForm {
+            coeffs : self.coeffs.iter()
+                .zip(&rhs.coeffs)
+                .map(|(a, b)| a.checked_add(*b))
+                .collect::<Option<Vec<i64>>>()?,
+            cst : self.cst.binary(IntBinaryOp::Add, &rhs.cst),
+        })
+    }
+
+    /// the form `self - rhs + shift`, used to turn comparisons into `f <= 0`.
+    /// Overflowing coefficients give the form 0, which carries no information.
+    pub fn diff(&self, rhs : &Self, shift : i64) -> Self {
+        let n = self.coeffs.len();
+        rhs.scale(-1)
+            .and_then(|rhs| self.add(&rhs))
+            .and_then(|f| f.add(&LinearForm::constant(n, Interval::singleton(shift))))
+            .unwrap_or_else(|| LinearForm::constant(n, Interval::singleton(0)))
+    }
+
+    /// evaluates the form in the interval domain.
+    pub fn eval<L : Linearizable>(&self, env : &L) -> Interval {
+        self.coeffs
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| **a != 0)
+            .fold(self.cst, |acc, (k, a)| {
+                acc.binary(IntBinaryOp::Add,
+                    &env.bounds(k).binary(IntBinaryOp::Mul, &Interval::singleton(*a)))
+            })
+    }
+
+    /// linearizes an expression, the non-linear parts being evaluated with intervals.
+    pub fn of_expr<L : Linearizable>(env : &L, e : &IntExpr) -> Result<Self, AnalysisError> {
+        let n = env.dimension();
+        match e {
+            IntExpr::Unary { op : IntUnaryOp::AddUnary, exp, .. } =>
+                Self::of_expr(env, exp),
+            IntExpr::Unary { op : IntUnaryOp::SubUnary, exp, .. } => {
+                let f = Self::of_expr(env, exp)?;
+                Ok(f.scale(-1).unwrap_or_else(|| LinearForm::constant(n, f.eval(env).unary(IntUnaryOp::SubUnary))))
+            },
+            IntExpr::Binary { op, lhs, rhs, .. } => {
+                let f1 = Self::of_expr(env, lhs)?;
+                let f2 = Self::of_expr(env, rhs)?;
+                let res =
+                    match op {
+                        IntBinaryOp::Add => f1.add(&f2),
+                        IntBinaryOp::Sub => f2.scale(-1).and_then(|f2| f1.add(&f2)),
+                        IntBinaryOp::Mul =>
+                            match (f1.is_constant(), f2.is_constant(), f1.cst, f2.cst) {
+                                (true, _, Interval::Itv(Bound::Finite(a), Bound::Finite(b)), _) if a == b =>
+                                    f2.scale(a),
+                                (_, true, _, Interval::Itv(Bound::Finite(a), Bound::Finite(b))) if a == b =>
+                                    f1.scale(a),
+                                _ => None,
+                            },
+                        IntBinaryOp::Div | IntBinaryOp::Mod => None,
+                    };
+                Ok(res.unwrap_or_else(|| {
+                    LinearForm::constant(n, f1.eval(env).binary(*op, &f2.eval(env)))
+                }))
+            },
+            IntExpr::Ident { var, .. } =>
+                Ok(LinearForm::variable(n, env.index(var)?)),
+            IntExpr::Const { cst, .. } =>
+                Ok(LinearForm::constant(n, cst.parse().map_or(Interval::top(), Interval::singleton))),
+            IntExpr::Rand { lower, upper, .. } => {
+                let l = Self::of_expr(env, lower)?.eval(env);
+                let u = Self::of_expr(env, upper)?.eval(env);
+                Ok(LinearForm::constant(n, Interval::rand(&l, &u)))
+            },
+        }
+    }
+}
@@ -8,5 +8,13 @@ pub mod value_domain;
 pub mod non_relational_domain;
 pub mod interval_domain;
 pub mod disjonctive;
+pub mod initialization;
+pub mod linear_form;
+pub mod octagon_domain;
+pub mod polyhedra_domain;
 pub mod concrete_domain;
-pub mod constant_domain;
\ No newline at end of file
+pub mod constant_domain;
+pub mod congruence_domain;
+
+#[cfg(test)]
+mod testing;
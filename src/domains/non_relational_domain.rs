@@ -2,4 +2,278 @@
  * author : Narcisse.
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
- */
\ No newline at end of file
+ */
+use crate::ast::*;
+use crate::symbol::*;
+use crate::interpreter::interpreter::AnalysisError;
+use crate::libs::map::{Map, MapTrait};
+
+use super::domain::AbstractDomain;
+use super::value_domain::ValueDomain;
+
+/// integer expression annotated with the forward values of its nodes,
+/// the children following the operands of the expression.
+struct Annotated<V : ValueDomain> {
+    value : V,
+    children : Vec<Annotated<V>>,
+}
+
+/// non-relational lifting of a value domain : every variable is mapped to
+/// an abstract value, environments where a variable has no value are
+/// collapsed into `Bottom`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum NonRelational<V : ValueDomain> {
+    Bottom,
+    Env(Map<V>),
+}
+
+impl<V> NonRelational<V>
+where V : ValueDomain {
+    /// forward evaluation of an integer expression in a non-bottom environment.
+    fn eval(env : &Map<V>, e : &IntExpr) -> Result<V, AnalysisError> {
+        match e {
+            IntExpr::Unary { op, exp, .. } =>
+                Ok(Self::eval(env, exp)?.unary(*op)),
+            IntExpr::Binary { op, lhs, rhs, .. } =>
+                Ok(Self::eval(env, lhs)?.binary(*op, &Self::eval(env, rhs)?)),
+            IntExpr::Ident { var, .. } =>
+                env.find(var).cloned().ok_or(AnalysisError::UnknownVariable),
+            // literals too large for the analysis can be anything
+            IntExpr::Const { cst, .. } =>
+                Ok(cst.parse().map_or(V::top(), V::constant)),
+            IntExpr::Rand { lower, upper, .. } =>
+                Ok(V::rand(&Self::eval(env, lower)?, &Self::eval(env, upper)?)),
+        }
+    }
+
+    /// forward evaluation of an integer expression, keeping the value of every node.
+    fn annotate(env : &Map<V>, e : &IntExpr) -> Result<Annotated<V>, AnalysisError> {
+        let children =
+            match e {
+                IntExpr::Unary { exp, .. } => vec![Self::annotate(env, exp)?],
+                IntExpr::Binary { lhs, rhs, .. } => vec![Self::annotate(env, lhs)?, Self::annotate(env, rhs)?],
+                IntExpr::Ident { .. } | IntExpr::Const { .. } | IntExpr::Rand { .. } => Vec::new(),
+            };
+        let value =
+            match (e, &children[..]) {
+                (IntExpr::Unary { op, .. }, [x]) => x.value.unary(*op),
+                (IntExpr::Binary { op, .. }, [x, y]) => x.value.binary(*op, &y.value),
+                _ => Self::eval(env, e)?,
+            };
+        Ok(Annotated { value, children })
+    }
+
+    /// backward evaluation of an integer expression annotated by `tree` : refines
+    /// the variables of `e` knowing that its value lies in `r`, from the root down
+    /// to the leaves (HC4-revise). Returns false when no value of `e` can be in `r`.
+    fn refine(env : &mut Map<V>, e : &IntExpr, tree : &Annotated<V>, r : &V) -> Result<bool, AnalysisError> {
+        let r = tree.value.meet(r);
+        if r.is_bottom() {
+            return Ok(false);
+        }
+        match (e, &tree.children[..]) {
+            (IntExpr::Unary { op, exp, .. }, [x]) => {
+                let rx = x.value.bwd_unary(*op, &r);
+                Self::refine(env, exp, x, &rx)
+            },
+            (IntExpr::Binary { op, lhs, rhs, .. }, [x, y]) => {
+                let (rx, ry) = x.value.bwd_binary(*op, &y.value, &r);
+                Ok(Self::refine(env, lhs, x, &rx)? && Self::refine(env, rhs, y, &ry)?)
+            },
+            (IntExpr::Ident { var, .. }, _) => {
+                let x = env.find(var).ok_or(AnalysisError::UnknownVariable)?.meet(&r);
+                let _ = env.add(var, &x);
+                Ok(!x.is_bottom())
+            },
+            _ => Ok(true),
+        }
+    }
+
+    /// collapses environments containing an empty value into bottom.
+    fn normalize(env : Map<V>) -> Self {
+        if env.for_all(|_, v| !v.is_bottom()) {
+            NonRelational::Env(env)
+        }
+        else {
+            NonRelational::Bottom
+        }
+    }
+
+    /// pointwise application of a binary operator on two environments.
+    fn pointwise<F : FnMut(&V, &V) -> V>(&mut self, rhs : Self, f : F) {
+        if let (NonRelational::Env(lhs), NonRelational::Env(rhs)) = (&mut *self, &rhs) {
+            lhs.map2z(rhs, f)
+                .expect("operands should be defined over the same variables");
+        }
+        if let NonRelational::Env(env) = self {
+            *self = Self::normalize(env.clone());
+        }
+    }
+}
+
+impl<V> AbstractDomain for NonRelational<V>
+where V : ValueDomain {
+    fn top() -> Self {
+        NonRelational::Env(Map::new())
+    }
+
+    fn bottom() -> Self {
+        NonRelational::Bottom
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, NonRelational::Bottom)
+    }
+
+    fn subset(&self, rhs : &Self) -> bool {
+        match (self, rhs) {
+            (NonRelational::Bottom, _) => true,
+            (_, NonRelational::Bottom) => false,
+            (NonRelational::Env(lhs), NonRelational::Env(rhs)) =>
+                lhs.clone()
+                    .for_all2z(rhs, |_, a, b| a.subset(b))
+                    .expect("operands should be defined over the same variables"),
+        }
+    }
+
+    fn join_with(&mut self, rhs : Self) {
+        match (&self, &rhs) {
+            (NonRelational::Bottom, _) => *self = rhs,
+            (_, NonRelational::Bottom) => (),
+            _ => self.pointwise(rhs, |a, b| a.join(b)),
+        }
+    }
+
+    fn meet_with(&mut self, rhs : Self) {
+        match (&self, &rhs) {
+            (NonRelational::Bottom, _) => (),
+            (_, NonRelational::Bottom) => *self = NonRelational::Bottom,
+            _ => self.pointwise(rhs, |a, b| a.meet(b)),
+        }
+    }
+
+    fn widen_with(&mut self, rhs : Self) {
+        match (&self, &rhs) {
+            (NonRelational::Bottom, _) => *self = rhs,
+            (_, NonRelational::Bottom) => (),
+            _ => self.pointwise(rhs, |a, b| a.widen(b)),
+        }
+    }
+
+    fn widen_thresholds_with(&mut self, rhs : Self, thresholds : &[i64]) {
+        match (&self, &rhs) {
+            (NonRelational::Bottom, _) => *self = rhs,
+            (_, NonRelational::Bottom) => (),
+            _ => self.pointwise(rhs, |a, b| a.widen_thresholds(b, thresholds)),
+        }
+    }
+
+    fn narrow_with(&mut self, rhs : Self) {
+        match (&self, &rhs) {
+            (NonRelational::Bottom, _) => (),
+            (_, NonRelational::Bottom) => *self = NonRelational::Bottom,
+            _ => self.pointwise(rhs, |a, b| a.narrow(b)),
+        }
+    }
+
+    fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self {
+        let NonRelational::Env(env) = self else {
+            return NonRelational::Bottom;
+        };
+        let (Ok(t1), Ok(t2)) = (Self::annotate(env, e1), Self::annotate(env, e2)) else {
+            return NonRelational::Bottom;
+        };
+        let (r1, r2) = t1.value.compare(cmp, &t2.value);
+        let mut env = env.clone();
+        match (Self::refine(&mut env, e1, &t1, &r1), Self::refine(&mut env, e2, &t2, &r2)) {
+            (Ok(true), Ok(true)) => Self::normalize(env),
+            _ => NonRelational::Bottom,
+        }
+    }
+
+    fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError> {
+        let NonRelational::Env(env) = self else {
+            return Ok(NonRelational::Bottom);
+        };
+        if !env.mem(v) {
+            return Err(AnalysisError::UnknownVariable);
+        }
+        let value = Self::eval(env, e)?;
+        let mut env = env.clone();
+        let _ = env.add(v, &value);
+        Ok(Self::normalize(env))
+    }
+
+    fn add_variable(&mut self, v : &Symbol) -> Self {
+        match self {
+            NonRelational::Bottom => NonRelational::Bottom,
+            NonRelational::Env(env) => {
+                let mut env = env.clone();
+                let _ = env.add(v, &V::top());
+                NonRelational::Env(env)
+            },
+        }
+    }
+
+    fn remove_variable(&mut self, v : &Symbol) -> Self {
+        match self {
+            NonRelational::Bottom => NonRelational::Bottom,
+            NonRelational::Env(env) => {
+                let mut env = env.clone();
+                env.remove(v);
+                NonRelational::Env(env)
+            },
+        }
+    }
+
+    fn forget(&mut self, v : &Symbol) -> Self {
+        match self {
+            NonRelational::Bottom => NonRelational::Bottom,
+            NonRelational::Env(env) => {
+                let mut env = env.clone();
+                let _ = env.add(v, &V::top());
+                NonRelational::Env(env)
+            },
+        }
+    }
+
+    fn print(&mut self, symbol : Symbol) -> String {
+        match self {
+            NonRelational::Bottom => V::bottom().to_string(),
+            NonRelational::Env(env) =>
+                env.find(&symbol).cloned().unwrap_or(V::bottom()).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domains::interval_domain::IntervalDomain;
+    use crate::domains::testing::value_of;
+
+    #[test]
+    fn test_backward_refinement() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x + 1 < 5); }", "x"), "[0;3]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(10 - x >= 5); }", "x"), "[0;5]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(-x > -3); }", "x"), "[0;2]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; int y; x = rand(0,10); y = rand(0,10); assert(x + y >= 19); }", "y"), "[9;10]");
+    }
+
+    #[test]
+    fn test_backward_multiplicative() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(2 * x + 1 < 7); }", "x"), "[0;2]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(-100,100); assert(x * 3 >= 10); }", "x"), "[3;100]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,100); assert(x / 3 == 2); }", "x"), "[6;8]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(-100,100); assert(x / -4 >= 5); }", "x"), "[-100;-20]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(-10,10); assert(x % 4 == 3); }", "x"), "[1;10]");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; int y; x = rand(0,100); y = rand(0,10); assert(x % y == 5); }", "y"), "[6;10]");
+        // the product may be 0 whatever the value of x
+        assert_eq!(value_of::<IntervalDomain>("{ int x; int y; x = rand(0,100); y = rand(0,1); assert(x * y <= 0); }", "x"), "[0;100]");
+    }
+
+    #[test]
+    fn test_unsatisfiable_guard() {
+        assert_eq!(value_of::<IntervalDomain>("{ int x; x = rand(0,10); assert(x + 1 > 20); }", "x"), "bot");
+        assert_eq!(value_of::<IntervalDomain>("{ int x; assert(3 < 2); }", "x"), "bot");
+    }
+}
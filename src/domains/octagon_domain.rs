@@ -0,0 +1,585 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ast::*;
+use crate::symbol::*;
+use crate::interpreter::interpreter::AnalysisError;
+
+use super::domain::AbstractDomain;
+use super::value_domain::ValueDomain;
+use super::interval_domain::{Bound, Interval};
+use super::linear_form::{LinearForm, Linearizable};
+
+/// bound of a constraint of the DBM, `None` standing for +oo.
+type Bnd = Option<i64>;
+
+/// converts an exact sum of bounds into a bound, overflows are rounded
+/// towards +oo which keeps the constraint sound.
+fn clamp(n : i128) -> Bnd {
+    i64::try_from(n).ok().or(if n < 0 { Some(i64::MIN) } else { None })
+}
+
+fn add(a : Bnd, b : Bnd) -> Bnd {
+    clamp(a? as i128 + b? as i128)
+}
+
+fn min(a : Bnd, b : Bnd) -> Bnd {
+    match (a, b) {
+        (None, x) | (x, None) => x,
+        (Some(a), Some(b)) => Some(a.min(b)),
+    }
+}
+
+fn max(a : Bnd, b : Bnd) -> Bnd {
+    Some(a?.max(b?))
+}
+
+fn le(a : Bnd, b : Bnd) -> bool {
+    match (a, b) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(a), Some(b)) => a <= b,
+    }
+}
+
+/// floor of half a bound.
+fn half(a : Bnd) -> Bnd {
+    a.map(|a| a.div_euclid(2))
+}
+
+fn upper(b : Bnd) -> Bound {
+    b.map_or(Bound::PlusInf, Bound::Finite)
+}
+
+fn lower(b : Bnd) -> Bound {
+    b.map_or(Bound::MinusInf, |b| Bound::of_i128(-(b as i128), true))
+}
+
+/// inverse of `upper`, infinite or overflowing bounds give no constraint.
+fn of_upper(b : Bound) -> Bnd {
+    match b {
+        Bound::Finite(b) => Some(b),
+        _ => None,
+    }
+}
+
+fn of_lower(b : Bound) -> Bnd {
+    match b {
+        Bound::Finite(b) => b.checked_neg(),
+        _ => None,
+    }
+}
+
+/// octagon over the variables `vars`, as a difference bound matrix over the
+/// 2n variables V(2k) = x(k) and V(2k+1) = -x(k) : the entry (i, j) bounds V(j) - V(i).
+/// Apart from the results of widenings, octagons are kept strongly closed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Octagon {
+    vars : Vec<Symbol>,
+    m : Vec<Bnd>,
+}
+
+impl Octagon {
+    fn dim(&self) -> usize {
+        2 * self.vars.len()
+    }
+
+    fn get(&self, i : usize, j : usize) -> Bnd {
+        self.m[i * self.dim() + j]
+    }
+
+    fn set(&mut self, i : usize, j : usize, b : Bnd) {
+        let dim = self.dim();
+        self.m[i * dim + j] = b;
+    }
+
+    /// adds the constraint V(j) - V(i) <= b, and its coherent version.
+    fn constrain(&mut self, i : usize, j : usize, b : Bnd) {
+        self.set(i, j, min(self.get(i, j), b));
+        self.set(j ^ 1, i ^ 1, min(self.get(j ^ 1, i ^ 1), b));
+    }
+
+    /// adds the constraint `sum s * x <= c` where every term (x, s)
+    /// has a sign s = 1 or -1, with at most two terms.
+    fn constrain_terms(&mut self, terms : &[(usize, i64)], c : i64) {
+        let pos = |(k, s) : (usize, i64)| if s > 0 { 2 * k } else { 2 * k + 1 };
+        match terms {
+            [t] => self.constrain(pos(*t) ^ 1, pos(*t), clamp(2 * c as i128)),
+            [t1, t2] => self.constrain(pos(*t1) ^ 1, pos(*t2), Some(c)),
+            _ => (),
+        }
+    }
+
+    /// tight closure for integer octagons (Bagnara, Hill and Zaffanella) :
+    /// shortest paths, tightening of the unary constraints, then strengthening.
+    /// Returns `None` when the octagon is empty.
+    fn close(mut self) -> Option<Self> {
+        let n = self.dim();
+        for k in 0..n {
+            for i in 0..n {
+                let mik = self.get(i, k);
+                if mik.is_none() {
+                    continue;
+                }
+                for j in 0..n {
+                    let path = add(mik, self.get(k, j));
+                    if !le(self.get(i, j), path) {
+                        self.set(i, j, path);
+                    }
+                }
+            }
+        }
+        if (0..n).any(|i| !le(Some(0), self.get(i, i))) {
+            return None;
+        }
+        for i in 0..n {
+            self.set(i, i ^ 1, half(self.get(i, i ^ 1)).map(|b| 2 * b));
+        }
+        for i in 0..n {
+            for j in 0..n {
+                let b = add(half(self.get(i, i ^ 1)), half(self.get(j ^ 1, j)));
+                self.set(i, j, min(self.get(i, j), b));
+            }
+        }
+        if (0..n).any(|i| !le(Some(0), add(self.get(i, i ^ 1), self.get(i ^ 1, i)))) {
+            return None;
+        }
+        for i in 0..n {
+            self.set(i, i, Some(0));
+        }
+        Some(self)
+    }
+
+    /// adds an unconstrained variable.
+    fn extend(&self, v : &Symbol) -> Self {
+        let mut res = Octagon { vars : self.vars.clone(), m : Vec::new() };
+        res.vars.push(v.clone());
+        let dim = res.dim();
+        res.m = vec![None; dim * dim];
+        for i in 0..self.dim() {
+            for j in 0..self.dim() {
+                res.set(i, j, self.get(i, j));
+            }
+        }
+        res.set(dim - 1, dim - 1, Some(0));
+        res.set(dim - 2, dim - 2, Some(0));
+        res
+    }
+
+    /// removes the variable of index k, the octagon should be closed.
+    fn project(&self, k : usize) -> Self {
+        let mut vars = self.vars.clone();
+        vars.remove(k);
+        let kept : Vec<usize> = (0..self.dim()).filter(|i| i / 2 != k).collect();
+        let m = kept.iter()
+            .flat_map(|i| kept.iter().map(move |j| (*i, *j)))
+            .map(|(i, j)| self.get(i, j))
+            .collect();
+        Octagon { vars, m }
+    }
+
+    /// removes all the constraints on the variable of index k.
+    fn forget(&mut self, k : usize) {
+        for i in 0..self.dim() {
+            for p in [2 * k, 2 * k + 1] {
+                if i != p {
+                    self.set(i, p, None);
+                    self.set(p, i, None);
+                }
+            }
+        }
+    }
+
+    /// interval of the variable of index k.
+    fn interval(&self, k : usize) -> Interval {
+        Interval::new(
+            lower(half(self.get(2 * k, 2 * k + 1))),
+            upper(half(self.get(2 * k + 1, 2 * k)))
+        )
+    }
+
+    /// adds the constraint `f <= 0` : exact when f has at most two variables
+    /// with unit coefficients, otherwise every one and two-variable octagonal
+    /// constraint implied by the interval of the remaining terms is added.
+    fn constrain_form(&mut self, f : &LinearForm) {
+        let Interval::Itv(cl, _) = f.cst else {
+            return;
+        };
+        let vars = f.vars();
+        // upper bound of `sum a * x` for the terms in `terms`, knowing the others
+        let bound = |oct : &Self, terms : &[usize]| -> Option<i64> {
+            let mut rest = f.clone();
+            for k in terms {
+                rest.coeffs[*k] = 0;
+            }
+            rest.cst = Interval::singleton(0);
+            let rest = rest.eval(oct);
+            let Interval::Itv(rl, _) = rest else {
+                return None;
+            };
+            of_upper(cl.add(rl, true).opp(false))
+        };
+        let mut constraints : Vec<(Vec<(usize, i64)>, i64)> = Vec::new();
+        for (idx, k) in vars.iter().enumerate() {
+            let a = f.coeffs[*k];
+            if let Some(c) = bound(self, &[*k]) {
+                constraints.push((vec![(*k, a.signum())], c.div_euclid(a.abs())));
+            }
+            for l in vars.iter().skip(idx + 1) {
+                let b = f.coeffs[*l];
+                if a.abs() == 1 && b.abs() == 1 {
+                    if let Some(c) = bound(self, &[*k, *l]) {
+                        constraints.push((vec![(*k, a), (*l, b)], c));
+                    }
+                }
+            }
+        }
+        for (terms, c) in constraints {
+            self.constrain_terms(&terms, c);
+        }
+    }
+
+    /// bounds of `x(k) + s * x(l)` for s = 1 or -1.
+    fn relation(&self, k : usize, l : usize, s : i64) -> Interval {
+        let q = if s > 0 { 2 * l + 1 } else { 2 * l };
+        Interval::new(lower(self.get(2 * k, q)), upper(self.get(q, 2 * k)))
+    }
+
+    /// evaluates a linear form, exactly for the octagonal ones.
+    fn eval_octagonal(&self, f : &LinearForm) -> Interval {
+        let vars = f.vars();
+        match vars[..] {
+            [k, l] if f.coeffs[k] == 1 && f.coeffs[l].abs() == 1 =>
+                self.relation(k, l, f.coeffs[l]).binary(IntBinaryOp::Add, &f.cst),
+            [k, l] if f.coeffs[k] == -1 && f.coeffs[l].abs() == 1 =>
+                self.relation(k, l, -f.coeffs[l])
+                    .unary(IntUnaryOp::SubUnary)
+                    .binary(IntBinaryOp::Add, &f.cst),
+            _ => f.eval(self),
+        }
+    }
+}
+
+/// octagon domain, capturing invariants of the form +-x +-y <= c.
+#[derive(Clone, PartialEq, Eq)]
+pub enum OctagonDomain {
+    Bottom,
+    Oct(Octagon),
+}
+
+impl Linearizable for Octagon {
+    fn dimension(&self) -> usize {
+        self.vars.len()
+    }
+
+    fn index(&self, v : &Symbol) -> Result<usize, AnalysisError> {
+        self.vars.iter().position(|x| x == v).ok_or(AnalysisError::UnknownVariable)
+    }
+
+    fn bounds(&self, k : usize) -> Interval {
+        self.interval(k)
+    }
+}
+
+impl OctagonDomain {
+    fn of_closure(oct : Octagon) -> Self {
+        oct.close().map_or(OctagonDomain::Bottom, OctagonDomain::Oct)
+    }
+
+    /// pointwise combination of two matrices over the same variables.
+    fn pointwise<F : Fn(Bnd, Bnd) -> Bnd>(lhs : &Octagon, rhs : &Octagon, f : F) -> Octagon {
+        assert!(lhs.vars == rhs.vars, "operands should be defined over the same variables");
+        Octagon {
+            vars : lhs.vars.clone(),
+            m : lhs.m.iter().zip(&rhs.m).map(|(a, b)| f(*a, *b)).collect(),
+        }
+    }
+
+    fn guard(oct : &Octagon, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Result<Self, AnalysisError> {
+        let f1 = LinearForm::of_expr(oct, e1)?;
+        let f2 = LinearForm::of_expr(oct, e2)?;
+        let n = oct.vars.len();
+        // every comparison is turned into constraints f <= 0
+        let forms =
+            match cmp {
+                CompareOp::LE => vec![f1.diff(&f2, 0)],
+                CompareOp::LT => vec![f1.diff(&f2, 1)],
+                CompareOp::GE => vec![f2.diff(&f1, 0)],
+                CompareOp::GT => vec![f2.diff(&f1, 1)],
+                CompareOp::EQ => vec![f1.diff(&f2, 0), f2.diff(&f1, 0)],
+                CompareOp::NE => {
+                    let f = f1.diff(&f2, 0);
+                    if oct.eval_octagonal(&f) == Interval::singleton(0) {
+                        return Ok(OctagonDomain::Bottom);
+                    }
+                    let vars = f.vars();
+                    match (&vars[..], f.cst) {
+                        // s * x + c != 0 excludes x = -s * c, which matters at the bounds of x
+                        ([k], Interval::Itv(Bound::Finite(c), Bound::Finite(d)))
+                            if c == d && f.coeffs[*k].abs() == 1 => {
+                            let v = -f.coeffs[*k] * c;
+                            let mut x = LinearForm::constant(n, Interval::singleton(-v));
+                            x.coeffs[*k] = 1;
+                            let mut forms = Vec::new();
+                            if oct.interval(*k).subset(&Interval::new(Bound::Finite(v), Bound::PlusInf))
+                                && oct.interval(*k).contains(v) {
+                                forms.push(LinearForm::constant(n, Interval::singleton(0)).diff(&x, 1));
+                            }
+                            if oct.interval(*k).subset(&Interval::new(Bound::MinusInf, Bound::Finite(v)))
+                                && oct.interval(*k).contains(v) {
+                                forms.push(x.diff(&LinearForm::constant(n, Interval::singleton(0)), 1));
+                            }
+                            forms
+                        },
+                        _ => Vec::new(),
+                    }
+                },
+            };
+        let mut res = oct.clone();
+        for f in forms {
+            if f.cst.is_bottom() {
+                return Ok(OctagonDomain::Bottom);
+            }
+            res.constrain_form(&f);
+        }
+        Ok(Self::of_closure(res))
+    }
+}
+
+impl AbstractDomain for OctagonDomain {
+    fn top() -> Self {
+        OctagonDomain::Oct(Octagon { vars : Vec::new(), m : Vec::new() })
+    }
+
+    fn bottom() -> Self {
+        OctagonDomain::Bottom
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, OctagonDomain::Bottom)
+    }
+
+    fn subset(&self, rhs : &Self) -> bool {
+        match (self, rhs) {
+            (OctagonDomain::Bottom, _) => true,
+            (_, OctagonDomain::Bottom) => false,
+            (OctagonDomain::Oct(lhs), OctagonDomain::Oct(rhs)) =>
+                lhs.m.iter().zip(&rhs.m).all(|(a, b)| le(*a, *b)),
+        }
+    }
+
+    fn join_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (OctagonDomain::Bottom, rhs) => *self = rhs,
+            (_, OctagonDomain::Bottom) => (),
+            (OctagonDomain::Oct(lhs), OctagonDomain::Oct(rhs)) =>
+                *self = OctagonDomain::Oct(Self::pointwise(lhs, &rhs, max)),
+        }
+    }
+
+    fn meet_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (OctagonDomain::Bottom, _) => (),
+            (_, OctagonDomain::Bottom) => *self = OctagonDomain::Bottom,
+            (OctagonDomain::Oct(lhs), OctagonDomain::Oct(rhs)) =>
+                *self = Self::of_closure(Self::pointwise(lhs, &rhs, min)),
+        }
+    }
+
+    // the result is not closed, closing it could break the termination
+    fn widen_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (OctagonDomain::Bottom, rhs) => *self = rhs,
+            (_, OctagonDomain::Bottom) => (),
+            (OctagonDomain::Oct(lhs), OctagonDomain::Oct(rhs)) =>
+                *self = OctagonDomain::Oct(Self::pointwise(lhs, &rhs, |a, b| {
+                    if le(b, a) { a } else { None }
+                })),
+        }
+    }
+
+    fn narrow_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (OctagonDomain::Bottom, _) => (),
+            (_, OctagonDomain::Bottom) => *self = OctagonDomain::Bottom,
+            (OctagonDomain::Oct(lhs), OctagonDomain::Oct(rhs)) =>
+                *self = Self::of_closure(Self::pointwise(lhs, &rhs, |a, b| {
+                    if a.is_none() { b } else { a }
+                })),
+        }
+    }
+
+    fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self {
+        let OctagonDomain::Oct(oct) = self else {
+            return OctagonDomain::Bottom;
+        };
+        // the operands of widenings may not be closed
+        let Some(oct) = oct.clone().close() else {
+            return OctagonDomain::Bottom;
+        };
+        Self::guard(&oct, e1, cmp, e2).unwrap_or(OctagonDomain::Bottom)
+    }
+
+    // x = e is computed as t = e; x = t for a fresh variable t, which handles
+    // uniformly the invertible assignments such as x = -x + c
+    fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError> {
+        let OctagonDomain::Oct(oct) = self else {
+            return Ok(OctagonDomain::Bottom);
+        };
+        let Some(oct) = oct.clone().close() else {
+            return Ok(OctagonDomain::Bottom);
+        };
+        let x = oct.index(v)?;
+        let f = LinearForm::of_expr(&oct, e)?;
+        let value = f.eval(&oct);
+        if value.is_bottom() {
+            return Ok(OctagonDomain::Bottom);
+        }
+        let n = oct.vars.len();
+        let mut res = oct.extend(&Symbol::Variable { name : String::new(), dtype : v.get_type().clone(), depth : 0 });
+        res.constrain_terms(&[(n, 1)], of_upper(match value { Interval::Itv(_, b) => b, _ => Bound::PlusInf }).unwrap_or(i64::MAX));
+        if let Interval::Itv(a, b) = value {
+            if let Some(a) = of_lower(a) {
+                res.constrain_terms(&[(n, -1)], a);
+            }
+            if b == Bound::PlusInf {
+                res.set(2 * n + 1, 2 * n, None);
+            }
+        }
+        for y in 0..n {
+            for s in [1, -1] {
+                // bounds of t - s * y = f - s * y
+                let mut g = f.clone();
+                g.coeffs[y] -= s;
+                if let Interval::Itv(a, b) = g.eval(&oct) {
+                    if let Some(b) = of_upper(b) {
+                        res.constrain_terms(&[(n, 1), (y, -s)], b);
+                    }
+                    if let Some(a) = of_lower(a) {
+                        res.constrain_terms(&[(n, -1), (y, s)], a);
+                    }
+                }
+            }
+        }
+        let Some(mut res) = res.close() else {
+            return Ok(OctagonDomain::Bottom);
+        };
+        res.forget(x);
+        res.constrain_terms(&[(x, 1), (n, -1)], 0);
+        res.constrain_terms(&[(x, -1), (n, 1)], 0);
+        Ok(res.close().map_or(OctagonDomain::Bottom, |res| OctagonDomain::Oct(res.project(n))))
+    }
+
+    fn add_variable(&mut self, v : &Symbol) -> Self {
+        let OctagonDomain::Oct(oct) = self else {
+            return OctagonDomain::Bottom;
+        };
+        Self::of_closure(oct.extend(v))
+    }
+
+    fn remove_variable(&mut self, v : &Symbol) -> Self {
+        let OctagonDomain::Oct(oct) = self else {
+            return OctagonDomain::Bottom;
+        };
+        match (oct.index(v), oct.clone().close()) {
+            (Ok(k), Some(oct)) => OctagonDomain::Oct(oct.project(k)),
+            (Err(_), Some(oct)) => OctagonDomain::Oct(oct),
+            (_, None) => OctagonDomain::Bottom,
+        }
+    }
+
+    fn forget(&mut self, v : &Symbol) -> Self {
+        let OctagonDomain::Oct(oct) = self else {
+            return OctagonDomain::Bottom;
+        };
+        match (oct.index(v), oct.clone().close()) {
+            (Ok(k), Some(mut oct)) => {
+                oct.forget(k);
+                OctagonDomain::Oct(oct)
+            },
+            (Err(_), Some(oct)) => OctagonDomain::Oct(oct),
+            (_, None) => OctagonDomain::Bottom,
+        }
+    }
+
+    // the interval of the variable, followed by its relations with the other
+    // variables that are more precise than what the intervals imply
+    fn print(&mut self, symbol : Symbol) -> String {
+        let OctagonDomain::Oct(oct) = self else {
+            return Interval::Bottom.to_string();
+        };
+        let (Ok(k), Some(oct)) = (oct.index(&symbol), oct.clone().close()) else {
+            return Interval::Bottom.to_string();
+        };
+        let mut res = vec![oct.interval(k).to_string()];
+        for l in (0..oct.vars.len()).filter(|l| *l != k && !oct.vars[*l].get_name().is_empty()) {
+            for (s, op, sign) in [(-1, IntBinaryOp::Sub, "-"), (1, IntBinaryOp::Add, "+")] {
+                let rel = oct.relation(k, l, s);
+                if rel != oct.interval(k).binary(op, &oct.interval(l)) {
+                    res.push(format!("{}{}{} in {}", symbol.get_name(), sign, oct.vars[l].get_name(), rel));
+                }
+            }
+        }
+        res.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::testing::{run, value_of};
+
+    #[test]
+    fn test_assign() {
+        assert_eq!(value_of::<OctagonDomain>("{ int x; x = rand(1,5) + rand(2,3); }", "x"), "[3;8]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; x = rand(0,10); y = x + 2; }", "y"), "[2;12], y-x in [2;2]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; x = rand(0,10); y = 5 - x; }", "y"), "[-5;5], y+x in [5;5]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; x = rand(0,10); x = -x + 3; }", "x"), "[-7;3]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; x = rand(0,10); y = x * x; }", "y"), "[0;100]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; y = 1; }", "x"), "[-inf;+inf]");
+    }
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; x = rand(10,20); y = x; assert(x >= 15); }", "y"), "[15;20], y-x in [0;0]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; x = rand(0,50); y = rand(0,50); assert(x < y); assert(y <= 3); }", "x"),
+            "[0;2], x-y in [-3;-1]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; x = rand(0,5); y = x; assert(x != y); }", "x"), "bot");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; x = rand(0,5); assert(x != 0); }", "x"), "[1;5]");
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; int z; x = rand(0,5); y = rand(0,5); z = rand(0,5); assert(x + y + z >= 14); }", "z"),
+            "[4;5], z+x in [9;10], z+y in [9;10]");
+        // the lower bound of the remaining term tightens the relation
+        assert_eq!(value_of::<OctagonDomain>("{ int x; int y; int z; x = rand(0,10); y = rand(0,10); z = rand(5,10); assert(x + y + z <= 10); }", "x"),
+            "[0;5], x+y in [0;5], x+z in [5;10]");
+    }
+
+    #[test]
+    fn test_join() {
+        let src = "{ int x; int y; x = rand(-10,25); y = rand(-15,20); if (x >= y) { x = y; } }";
+        assert_eq!(value_of::<OctagonDomain>(src, "x"), "[-15;20], x-y in [-30;0]");
+    }
+
+    #[test]
+    fn test_widen() {
+        let (state, symbols) = run::<OctagonDomain>("{ int x; int n; n = rand(0,50); x = 0; assert(x <= n); }");
+        let x = &symbols["x"];
+        let incr = parse_incr(x);
+        let next = state.clone().assign(x, &incr).unwrap();
+        let mut widened = state.clone().widen(state.clone().join(next));
+        // x - n <= 0 is unstable, x >= 0 and n in [0;50] are kept
+        assert_eq!(widened.print(x.clone()), "[0;+inf]");
+        assert_eq!(widened.print(symbols["n"].clone()), "[0;50]");
+    }
+
+    fn parse_incr(x : &Symbol) -> IntExpr {
+        let span = lrpar::Span::new(0, 0);
+        IntExpr::Binary {
+            span,
+            op : IntBinaryOp::Add,
+            lhs : Box::new(IntExpr::Ident { span, var : x.clone() }),
+            rhs : Box::new(IntExpr::Const { span, cst : "1".to_string() }),
+        }
+    }
+}
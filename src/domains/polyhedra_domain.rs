@@ -0,0 +1,669 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use crate::ast::*;
+use crate::symbol::*;
+use crate::interpreter::interpreter::AnalysisError;
+use crate::libs::rational::{gcd, Rational};
+
+use super::domain::AbstractDomain;
+use super::value_domain::ValueDomain;
+use super::interval_domain::{Bound, Interval};
+use super::linear_form::{LinearForm, Linearizable};
+
+fn dot(u : &[i128], w : &[i128]) -> Option<i128> {
+    u.iter().zip(w).try_fold(0i128, |acc, (a, b)| acc.checked_add(a.checked_mul(*b)?))
+}
+
+fn unit(n : usize, k : usize) -> Vec<i128> {
+    let mut v = vec![0; n];
+    v[k] = 1;
+    v
+}
+
+fn negate(v : &[i128]) -> Option<Vec<i128>> {
+    v.iter().map(|a| a.checked_neg()).collect()
+}
+
+/// divides the vector by the gcd of its entries.
+fn normalize(mut v : Vec<i128>) -> Vec<i128> {
+    let g = v.iter().fold(0, |g, a| gcd(g, *a));
+    if g > 1 {
+        v.iter_mut().for_each(|a| *a /= g);
+    }
+    v
+}
+
+/// the normalized vector a * u + b * w.
+fn combine(a : i128, u : &[i128], b : i128, w : &[i128]) -> Option<Vec<i128>> {
+    let v = u.iter()
+        .zip(w)
+        .map(|(x, y)| a.checked_mul(*x)?.checked_add(b.checked_mul(*y)?))
+        .collect::<Option<Vec<i128>>>()?;
+    Some(normalize(v))
+}
+
+/// row of a homogeneous system over the variables and an extra dimension xi :
+/// the polyhedron `a . x <= b` is the cone `-a . x + b * xi >= 0, xi >= 0`.
+/// A row is either a constraint `v . z >= 0` (`v . z = 0` when `eq` is set),
+/// or a generator of the cone (a line when `eq` is set). Generators with
+/// xi > 0 are the vertices, the other ones are rays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    v : Vec<i128>,
+    eq : bool,
+}
+
+impl Row {
+    /// the constraint `f <= 0`, `None` when it carries no information.
+    /// Its constant is rounded, which only removes non-integer points.
+    fn of_form(f : &LinearForm) -> Option<Self> {
+        let Interval::Itv(Bound::Finite(l), _) = f.cst else {
+            return None;
+        };
+        let g = f.coeffs.iter().fold(0, |g, a| gcd(g, *a as i128)).max(1);
+        let mut v : Vec<i128> = f.coeffs.iter().map(|a| -(*a as i128) / g).collect();
+        v.push((-(l as i128)).div_euclid(g));
+        Some(Row { v, eq : false })
+    }
+
+    /// both inequalities of an equality.
+    fn split(&self) -> Vec<Row> {
+        if !self.eq {
+            return vec![self.clone()];
+        }
+        let mut res = vec![Row { v : self.v.clone(), eq : false }];
+        res.extend(negate(&self.v).map(|v| Row { v, eq : false }));
+        res
+    }
+}
+
+/// whether the generator g satisfies the constraint c, overflows being
+/// answered negatively.
+fn satisfies(g : &Row, c : &Row) -> bool {
+    match dot(&g.v, &c.v) {
+        Some(0) => true,
+        Some(d) => d > 0 && !g.eq && !c.eq,
+        None => false,
+    }
+}
+
+/// set of the constraints saturated by a ray, as a bit vector.
+#[derive(Clone)]
+struct Saturation(Vec<u64>);
+
+impl Saturation {
+    /// the constraints 0 to n - 1.
+    fn full(n : usize) -> Self {
+        let mut res = Saturation(vec![u64::MAX; n / 64]);
+        if !n.is_multiple_of(64) {
+            res.0.push((1 << (n % 64)) - 1);
+        }
+        res
+    }
+
+    fn set(&mut self, j : usize) {
+        if self.0.len() <= j / 64 {
+            self.0.resize(j / 64 + 1, 0);
+        }
+        self.0[j / 64] |= 1 << (j % 64);
+    }
+
+    fn and(&self, rhs : &Self) -> Self {
+        Saturation(self.0.iter().zip(&rhs.0).map(|(a, b)| a & b).collect())
+    }
+
+    fn is_subset(&self, rhs : &Self) -> bool {
+        self.0.iter()
+            .enumerate()
+            .all(|(i, a)| a & !rhs.0.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    fn count(&self) -> usize {
+        self.0.iter().map(|a| a.count_ones() as usize).sum()
+    }
+}
+
+/// generators of the cone `{ z | r . z >= 0 for the rows r }` of dimension
+/// `dim`, computed by Chernikova's algorithm : the rows are added one by one
+/// to the generators of the whole space, and the rays on both sides of a row
+/// are combined when they are adjacent. Dually, the constraints of the cone
+/// spanned by some generators are computed from these generators. The result
+/// is minimal, and `None` is returned on overflows.
+fn chernikova(dim : usize, rows : &[Row]) -> Option<Vec<Row>> {
+    let mut lines : Vec<Vec<i128>> = (0..dim).map(|k| unit(dim, k)).collect();
+    let mut rays : Vec<(Vec<i128>, Saturation)> = Vec::new();
+    for (j, h) in rows.iter().enumerate() {
+        // a line crossing the hyperplane becomes a ray, or disappears for equalities
+        if let Some(i) = lines.iter().position(|l| dot(&h.v, l) != Some(0)) {
+            let mut l = lines.swap_remove(i);
+            let mut hl = dot(&h.v, &l)?;
+            if hl < 0 {
+                l = negate(&l)?;
+                hl = hl.checked_neg()?;
+            }
+            for m in lines.iter_mut() {
+                let hm = dot(&h.v, m)?;
+                if hm != 0 {
+                    *m = combine(hl, m, hm.checked_neg()?, &l)?;
+                }
+            }
+            for (r, sat) in rays.iter_mut() {
+                let hr = dot(&h.v, r)?;
+                if hr != 0 {
+                    *r = combine(hl, r, hr.checked_neg()?, &l)?;
+                }
+                sat.set(j);
+            }
+            if !h.eq {
+                rays.push((l, Saturation::full(j)));
+            }
+            continue;
+        }
+        let values = rays.iter().map(|(r, _)| dot(&h.v, r)).collect::<Option<Vec<i128>>>()?;
+        // adjacent rays saturate together enough constraints, and no other
+        // ray saturates all of them
+        let needed = (dim - lines.len()).saturating_sub(2);
+        let mut next = Vec::new();
+        for p in (0..rays.len()).filter(|p| values[*p] > 0) {
+            for n in (0..rays.len()).filter(|n| values[*n] < 0) {
+                let mut common = rays[p].1.and(&rays[n].1);
+                let adjacent =
+                    common.count() >= needed &&
+                    (0..rays.len()).all(|r| r == p || r == n || !common.is_subset(&rays[r].1));
+                if adjacent {
+                    common.set(j);
+                    next.push((combine(values[p], &rays[n].0, values[n].checked_neg()?, &rays[p].0)?, common));
+                }
+            }
+        }
+        for (i, (r, mut sat)) in rays.into_iter().enumerate() {
+            if values[i] == 0 {
+                sat.set(j);
+                next.push((r, sat));
+            }
+            else if values[i] > 0 && !h.eq {
+                next.push((r, sat));
+            }
+        }
+        rays = next;
+    }
+    Some(lines.into_iter()
+        .map(|v| Row { v, eq : true })
+        .chain(rays.into_iter().map(|(v, _)| Row { v, eq : false }))
+        .collect())
+}
+
+/// substitutes each equality in the other constraints, for its last variable
+/// among the first n ones : the constraints then have a canonical form.
+fn reduce(n : usize, mut cons : Vec<Row>) -> Option<Vec<Row>> {
+    let mut pivots = Vec::new();
+    for i in 0..cons.len() {
+        if !cons[i].eq {
+            continue;
+        }
+        let Some(k) = (0..n).rev().find(|k| cons[i].v[*k] != 0 && !pivots.contains(k)) else {
+            continue;
+        };
+        pivots.push(k);
+        if cons[i].v[k] < 0 {
+            cons[i].v = negate(&cons[i].v)?;
+        }
+        let e = cons[i].clone();
+        for (j, c) in cons.iter_mut().enumerate() {
+            if j != i && c.v[k] != 0 {
+                c.v = combine(e.v[k], &c.v, c.v[k].checked_neg()?, &e.v)?;
+            }
+        }
+    }
+    Some(cons)
+}
+
+/// non-empty convex polyhedron over the variables `vars`, in double description :
+/// minimal systems of constraints and of generators of the associated cone.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Polyhedron {
+    vars : Vec<Symbol>,
+    cons : Vec<Row>,
+    gens : Vec<Row>,
+}
+
+impl Linearizable for Polyhedron {
+    fn dimension(&self) -> usize {
+        self.vars.len()
+    }
+
+    fn index(&self, v : &Symbol) -> Result<usize, AnalysisError> {
+        self.vars.iter().position(|x| x == v).ok_or(AnalysisError::UnknownVariable)
+    }
+
+    // integer bounds of the variable, overflowing bounds being infinite
+    fn bounds(&self, k : usize) -> Interval {
+        let (lower, upper) = self.range(&unit(self.vars.len(), k));
+        Interval::new(
+            lower.and_then(|l| i64::try_from(l.ceil()).ok()).map_or(Bound::MinusInf, Bound::Finite),
+            upper.and_then(|u| i64::try_from(u.floor()).ok()).map_or(Bound::PlusInf, Bound::Finite)
+        )
+    }
+}
+
+impl Polyhedron {
+    /// the whole space.
+    fn universe(vars : Vec<Symbol>) -> Self {
+        let n = vars.len();
+        let mut gens : Vec<Row> = (0..n).map(|k| Row { v : unit(n + 1, k), eq : true }).collect();
+        gens.push(Row { v : unit(n + 1, n), eq : false });
+        Polyhedron { vars, cons : vec![Row { v : unit(n + 1, n), eq : false }], gens }
+    }
+
+    /// the polyhedron defined by some constraints, `None` on overflows.
+    fn of_cons(vars : Vec<Symbol>, mut cons : Vec<Row>) -> Option<PolyhedraDomain> {
+        let n = vars.len();
+        cons.push(Row { v : unit(n + 1, n), eq : false });
+        let gens = chernikova(n + 1, &cons)?;
+        if !gens.iter().any(|g| g.v[n] > 0) {
+            return Some(PolyhedraDomain::Bottom);
+        }
+        let cons = chernikova(n + 1, &gens)?;
+        let cons = reduce(n, cons.clone()).unwrap_or(cons);
+        Some(PolyhedraDomain::Poly(Polyhedron { vars, cons, gens }))
+    }
+
+    /// the polyhedron spanned by some generators, `None` on overflows.
+    fn of_gens(vars : Vec<Symbol>, gens : Vec<Row>) -> Option<PolyhedraDomain> {
+        let n = vars.len();
+        if !gens.iter().any(|g| g.v[n] > 0) {
+            return Some(PolyhedraDomain::Bottom);
+        }
+        let cons = chernikova(n + 1, &gens)?;
+        let gens = chernikova(n + 1, &cons)?;
+        let cons = reduce(n, cons.clone()).unwrap_or(cons);
+        Some(PolyhedraDomain::Poly(Polyhedron { vars, cons, gens }))
+    }
+
+    /// adds the constraints `f <= 0` for the forms of `forms`.
+    fn guard(&self, forms : Vec<LinearForm>) -> PolyhedraDomain {
+        let mut cons = self.cons.clone();
+        for f in forms {
+            if f.cst.is_bottom() {
+                return PolyhedraDomain::Bottom;
+            }
+            cons.extend(Row::of_form(&f));
+        }
+        // ignoring the constraints is sound on overflows
+        Polyhedron::of_cons(self.vars.clone(), cons).unwrap_or_else(|| PolyhedraDomain::Poly(self.clone()))
+    }
+
+    /// image of the generators by x(k) = f.
+    fn image(&self, k : usize, f : &LinearForm) -> Option<Vec<Row>> {
+        let n = self.vars.len();
+        let coeffs : Vec<i128> = f.coeffs.iter().map(|a| *a as i128).collect();
+        let Interval::Itv(l, u) = f.cst else {
+            return Some(Vec::new());
+        };
+        // the constant interval is base + [0; width], or a half-line or a line from base
+        let (base, width) =
+            match (l, u) {
+                (Bound::Finite(l), Bound::Finite(u)) => (l as i128, u as i128 - l as i128),
+                (Bound::Finite(l), _) => (l as i128, 0),
+                (_, Bound::Finite(u)) => (u as i128, 0),
+                _ => (0, 0),
+            };
+        let mut gens = Vec::new();
+        for g in &self.gens {
+            let mut v = g.v.clone();
+            v[k] = dot(&g.v[..n], &coeffs)?.checked_add(base.checked_mul(g.v[n])?)?;
+            if width > 0 && g.v[n] > 0 {
+                let mut w = v.clone();
+                w[k] = v[k].checked_add(width.checked_mul(g.v[n])?)?;
+                gens.push(Row { v : normalize(w), eq : false });
+            }
+            gens.push(Row { v : normalize(v), eq : g.eq });
+        }
+        match (l, u) {
+            (Bound::Finite(_), Bound::Finite(_)) => (),
+            (Bound::Finite(_), _) => gens.push(Row { v : unit(n + 1, k), eq : false }),
+            (_, Bound::Finite(_)) => gens.push(Row { v : negate(&unit(n + 1, k))?, eq : false }),
+            _ => gens.push(Row { v : unit(n + 1, k), eq : true }),
+        }
+        Some(gens)
+    }
+
+    /// range of the rational values of `sum coeffs * x`, `None` when it is unbounded
+    /// or on overflows.
+    fn range(&self, coeffs : &[i128]) -> (Option<Rational>, Option<Rational>) {
+        let n = self.vars.len();
+        let (mut lower, mut upper) : (Option<Rational>, Option<Rational>) = (None, None);
+        let (mut below, mut above) = (true, true);
+        for g in &self.gens {
+            let Some(d) = dot(&g.v[..n], coeffs) else {
+                return (None, None);
+            };
+            if g.v[n] == 0 {
+                above &= d == 0 || (d < 0 && !g.eq);
+                below &= d == 0 || (d > 0 && !g.eq);
+                continue;
+            }
+            let Some(q) = Rational::new(d, g.v[n]) else {
+                return (None, None);
+            };
+            lower = Some(lower.map_or(q, |l| l.min(q)));
+            upper = Some(upper.map_or(q, |u| u.max(q)));
+        }
+        (lower.filter(|_| below), upper.filter(|_| above))
+    }
+
+    /// pretty printing of a constraint, with a positive coefficient for the variable k.
+    fn show(&self, c : &Row, k : usize) -> String {
+        let n = self.vars.len();
+        // v . x + v(n) >= 0 is shown as v . x >= -v(n), or -v . x <= v(n)
+        let (coeffs, op, cst) : (Vec<i128>, &str, i128) =
+            if c.v[k] > 0 {
+                (c.v[..n].to_vec(), if c.eq { "==" } else { ">=" }, c.v[n].saturating_neg())
+            }
+            else {
+                (c.v[..n].iter().map(|a| a.saturating_neg()).collect(), if c.eq { "==" } else { "<=" }, c.v[n])
+            };
+        let mut res = String::new();
+        let order = std::iter::once(k).chain((0..n).filter(|l| *l != k));
+        for (a, v) in order.filter_map(|l| Some((*coeffs.get(l)?, &self.vars[l]))).filter(|(a, _)| *a != 0) {
+            let sign = if a < 0 { "-" } else if res.is_empty() { "" } else { "+" };
+            let spaced = if res.is_empty() { sign.to_string() } else { format!(" {} ", sign) };
+            let factor = if a.unsigned_abs() == 1 { String::new() } else { format!("{}*", a.unsigned_abs()) };
+            res.push_str(&format!("{}{}{}", spaced, factor, v.get_name()));
+        }
+        format!("{} {} {}", res, op, cst)
+    }
+}
+
+/// convex polyhedra domain, capturing invariants of the form sum a * x <= c.
+#[derive(Clone, PartialEq, Eq)]
+pub enum PolyhedraDomain {
+    Bottom,
+    Poly(Polyhedron),
+}
+
+impl AbstractDomain for PolyhedraDomain {
+    fn top() -> Self {
+        PolyhedraDomain::Poly(Polyhedron::universe(Vec::new()))
+    }
+
+    fn bottom() -> Self {
+        PolyhedraDomain::Bottom
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, PolyhedraDomain::Bottom)
+    }
+
+    fn subset(&self, rhs : &Self) -> bool {
+        match (self, rhs) {
+            (PolyhedraDomain::Bottom, _) => true,
+            (_, PolyhedraDomain::Bottom) => false,
+            (PolyhedraDomain::Poly(lhs), PolyhedraDomain::Poly(rhs)) =>
+                lhs.gens.iter().all(|g| rhs.cons.iter().all(|c| satisfies(g, c))),
+        }
+    }
+
+    fn join_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (PolyhedraDomain::Bottom, rhs) => *self = rhs,
+            (_, PolyhedraDomain::Bottom) => (),
+            (PolyhedraDomain::Poly(lhs), PolyhedraDomain::Poly(rhs)) => {
+                assert!(lhs.vars == rhs.vars, "operands should be defined over the same variables");
+                let mut gens = lhs.gens.clone();
+                gens.extend(rhs.gens);
+                // on overflows, the hull is approximated by the whole space
+                *self = Polyhedron::of_gens(lhs.vars.clone(), gens)
+                    .unwrap_or_else(|| PolyhedraDomain::Poly(Polyhedron::universe(lhs.vars.clone())));
+            },
+        }
+    }
+
+    fn meet_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (PolyhedraDomain::Bottom, _) => (),
+            (_, PolyhedraDomain::Bottom) => *self = PolyhedraDomain::Bottom,
+            (PolyhedraDomain::Poly(lhs), PolyhedraDomain::Poly(rhs)) => {
+                assert!(lhs.vars == rhs.vars, "operands should be defined over the same variables");
+                let mut cons = lhs.cons.clone();
+                cons.extend(rhs.cons);
+                if let Some(res) = Polyhedron::of_cons(lhs.vars.clone(), cons) {
+                    *self = res;
+                }
+            },
+        }
+    }
+
+    // standard widening : the constraints of self that still hold in rhs, and
+    // the constraints of rhs that could replace one of them in self, that is
+    // which saturate the same generators of self
+    fn widen_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (PolyhedraDomain::Bottom, rhs) => *self = rhs,
+            (_, PolyhedraDomain::Bottom) => (),
+            (PolyhedraDomain::Poly(lhs), PolyhedraDomain::Poly(rhs)) => {
+                let saturated = |c : &Row| -> Vec<bool> {
+                    lhs.gens.iter().map(|g| dot(&g.v, &c.v) == Some(0)).collect()
+                };
+                let old : Vec<Row> = lhs.cons.iter().flat_map(Row::split).collect();
+                let mut cons : Vec<Row> =
+                    old.iter().filter(|c| rhs.gens.iter().all(|g| satisfies(g, c))).cloned().collect();
+                for c in rhs.cons.iter().flat_map(Row::split) {
+                    let mutual =
+                        !cons.contains(&c) &&
+                        lhs.gens.iter().all(|g| satisfies(g, &c)) &&
+                        old.iter().any(|d| saturated(d) == saturated(&c));
+                    if mutual {
+                        cons.push(c);
+                    }
+                }
+                *self = Polyhedron::of_cons(lhs.vars.clone(), cons)
+                    .unwrap_or_else(|| PolyhedraDomain::Poly(Polyhedron::universe(lhs.vars.clone())));
+            },
+        }
+    }
+
+    // refines the directions where self is unbounded
+    fn narrow_with(&mut self, rhs : Self) {
+        match (&*self, rhs) {
+            (PolyhedraDomain::Bottom, _) => (),
+            (_, PolyhedraDomain::Bottom) => *self = PolyhedraDomain::Bottom,
+            (PolyhedraDomain::Poly(lhs), PolyhedraDomain::Poly(rhs)) => {
+                let n = lhs.vars.len();
+                let mut cons = lhs.cons.clone();
+                cons.extend(rhs.cons.iter().flat_map(Row::split).filter(|c| {
+                    lhs.gens.iter().any(|g| g.v[n] == 0 && !satisfies(g, c))
+                }));
+                if let Some(res) = Polyhedron::of_cons(lhs.vars.clone(), cons) {
+                    *self = res;
+                }
+            },
+        }
+    }
+
+    fn compare(&mut self, e1 : &IntExpr, cmp : &CompareOp, e2 : &IntExpr) -> Self {
+        let PolyhedraDomain::Poly(poly) = self else {
+            return PolyhedraDomain::Bottom;
+        };
+        let (Ok(f1), Ok(f2)) = (LinearForm::of_expr(poly, e1), LinearForm::of_expr(poly, e2)) else {
+            return PolyhedraDomain::Bottom;
+        };
+        // every comparison is turned into constraints f <= 0
+        let forms =
+            match cmp {
+                CompareOp::LE => vec![f1.diff(&f2, 0)],
+                CompareOp::LT => vec![f1.diff(&f2, 1)],
+                CompareOp::GE => vec![f2.diff(&f1, 0)],
+                CompareOp::GT => vec![f2.diff(&f1, 1)],
+                CompareOp::EQ => vec![f1.diff(&f2, 0), f2.diff(&f1, 0)],
+                CompareOp::NE => {
+                    let f = f1.diff(&f2, 0);
+                    let Interval::Itv(Bound::Finite(c), Bound::Finite(d)) = f.cst else {
+                        return if f.cst.is_bottom() { PolyhedraDomain::Bottom } else { self.clone() };
+                    };
+                    if c != d {
+                        return self.clone();
+                    }
+                    // l + c != 0 removes the bounds of l that are equal to -c
+                    let coeffs : Vec<i128> = f.coeffs.iter().map(|a| *a as i128).collect();
+                    let (lower, upper) = poly.range(&coeffs);
+                    let target = Rational::integer(-(c as i128));
+                    let zero = LinearForm::constant(f.coeffs.len(), Interval::singleton(0));
+                    let mut forms = Vec::new();
+                    if lower == Some(target) {
+                        forms.push(zero.diff(&f, 1));
+                    }
+                    if upper == Some(target) {
+                        forms.push(f.diff(&zero, 1));
+                    }
+                    forms
+                },
+            };
+        poly.guard(forms)
+    }
+
+    // the generators are moved by x = f, which is then extended to the
+    // whole range of the constant of f
+    fn assign(&mut self, v : &Symbol, e : &IntExpr) -> Result<Self, AnalysisError> {
+        let PolyhedraDomain::Poly(poly) = self else {
+            return Ok(PolyhedraDomain::Bottom);
+        };
+        let k = poly.index(v)?;
+        let f = LinearForm::of_expr(poly, e)?;
+        if f.cst.is_bottom() {
+            return Ok(PolyhedraDomain::Bottom);
+        }
+        let res = poly.image(k, &f).and_then(|gens| Polyhedron::of_gens(poly.vars.clone(), gens));
+        Ok(res.unwrap_or_else(|| PolyhedraDomain::Poly(Polyhedron::universe(poly.vars.clone()))))
+    }
+
+    fn add_variable(&mut self, v : &Symbol) -> Self {
+        let PolyhedraDomain::Poly(poly) = self else {
+            return PolyhedraDomain::Bottom;
+        };
+        let n = poly.vars.len();
+        let mut vars = poly.vars.clone();
+        vars.push(v.clone());
+        let mut gens = poly.gens.clone();
+        for g in gens.iter_mut() {
+            g.v.insert(n, 0);
+        }
+        // the new variable may have any value
+        gens.push(Row { v : unit(n + 2, n), eq : true });
+        Polyhedron::of_gens(vars.clone(), gens)
+            .unwrap_or_else(|| PolyhedraDomain::Poly(Polyhedron::universe(vars)))
+    }
+
+    fn remove_variable(&mut self, v : &Symbol) -> Self {
+        let PolyhedraDomain::Poly(poly) = self else {
+            return PolyhedraDomain::Bottom;
+        };
+        let Ok(k) = poly.index(v) else {
+            return self.clone();
+        };
+        let mut vars = poly.vars.clone();
+        vars.remove(k);
+        let gens = poly.gens.iter()
+            .map(|g| {
+                let mut v = g.v.clone();
+                v.remove(k);
+                Row { v : normalize(v), eq : g.eq }
+            })
+            .collect();
+        Polyhedron::of_gens(vars.clone(), gens)
+            .unwrap_or_else(|| PolyhedraDomain::Poly(Polyhedron::universe(vars)))
+    }
+
+    // the generators are extended by the line of the variable
+    fn forget(&mut self, v : &Symbol) -> Self {
+        let PolyhedraDomain::Poly(poly) = self else {
+            return PolyhedraDomain::Bottom;
+        };
+        let Ok(k) = poly.index(v) else {
+            return self.clone();
+        };
+        let n = poly.vars.len();
+        let mut gens = poly.gens.clone();
+        gens.push(Row { v : unit(n + 1, k), eq : true });
+        Polyhedron::of_gens(poly.vars.clone(), gens)
+            .unwrap_or_else(|| PolyhedraDomain::Poly(Polyhedron::universe(poly.vars.clone())))
+    }
+
+    // the interval of the variable, followed by the relational constraints on it
+    fn print(&mut self, symbol : Symbol) -> String {
+        let PolyhedraDomain::Poly(poly) = self else {
+            return Interval::Bottom.to_string();
+        };
+        let Ok(k) = poly.index(&symbol) else {
+            return Interval::Bottom.to_string();
+        };
+        let n = poly.vars.len();
+        let mut res = vec![poly.bounds(k).to_string()];
+        for c in poly.cons.iter().filter(|c| c.v[k] != 0 && c.v[..n].iter().filter(|a| **a != 0).count() > 1) {
+            res.push(poly.show(c, k));
+        }
+        res.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::testing::{run, step, value_of};
+
+    #[test]
+    fn test_assign() {
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; x = rand(1,5) + rand(2,3); }", "x"), "[3;8]");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; int y; x = rand(0,10); y = 2 * x + 1; }", "y"), "[1;21], y - 2*x == 1");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; x = rand(0,10); x = 3 - x; }", "x"), "[-7;3]");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; int y; x = rand(0,10); y = x * x; }", "y"), "[0;100]");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; x = 1 / 0; }", "x"), "bot");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; int y; y = 1; }", "x"), "[-inf;+inf]");
+    }
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; int y; int z; x = rand(0,10); y = rand(0,10); z = x + y; assert(z >= 18); }", "x"),
+            "[8;10], x + y - z == 0, x + y >= 18");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; int y; x = rand(0,5); y = x; assert(x != y); }", "x"), "bot");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; int y; x = rand(0,5); y = x + 1; assert(x + y != 1); }", "x"), "[1;5], x - y == -1");
+        assert_eq!(value_of::<PolyhedraDomain>("{ int x; x = rand(0,5); assert(2 * x == 3); }", "x"), "bot");
+    }
+
+    #[test]
+    fn test_join() {
+        let src = "{ int x; int y; if (rand(0,1) == 0) { x = 0; y = 0; } else { x = 4; y = 8; } }";
+        assert_eq!(value_of::<PolyhedraDomain>(src, "y"), "[0;8], y - 2*x == 0");
+        let src = "{ int x; int y; x = rand(-10,25); y = rand(-15,20); if (x >= y) { x = y; } }";
+        assert_eq!(value_of::<PolyhedraDomain>(src, "x"), "[-15;20], 7*x - y >= -90, x - y <= 0");
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let src = "{ int X; int Y; int S; int D; int R; Y = 0; }";
+        let body = "{ int X; int Y; int S; int D; int R;
+            X = rand(-128,128);
+            D = rand(0,16);
+            S = Y;
+            R = X - S;
+            Y = X;
+            if (R <= -D) { Y = S - D; } else { if (R >= D) { Y = S + D; } } }";
+        let (mut entry, symbols) = run::<PolyhedraDomain>(src);
+        // six unrolled iterations, three delayed joins, then widening
+        for _ in 0..6 {
+            entry = step(entry, body);
+        }
+        let mut inv = entry.clone();
+        for i in 0.. {
+            let next = entry.clone().join(step(inv.clone(), body));
+            if next.subset(&inv) {
+                break;
+            }
+            inv = if i < 3 { inv.join(next) } else { inv.widen(next) };
+        }
+        assert_eq!(inv.print(symbols["Y"].clone()).split(',').next(), Some("[-128;128]"));
+    }
+}
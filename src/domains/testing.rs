@@ -0,0 +1,65 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+//! helpers shared by the unit tests of the domains.
+use std::collections::HashMap;
+use crate::ast::*;
+use crate::symbol::Symbol;
+use crate::frontend::file_parser::parse_string;
+
+use super::domain::AbstractDomain;
+
+fn exec<D : AbstractDomain>(state : D, node : &TNode, symbols : &mut HashMap<String, Symbol>) -> D {
+    match node {
+        TNode::Block { decl, stmt, .. } => {
+            let mut state = state;
+            for var in decl {
+                symbols.insert(var.get_name().to_string(), var.clone());
+                state = state.add_variable(var);
+            }
+            stmt.iter().fold(state, |state, s| exec(state, s, symbols))
+        },
+        TNode::Assign { lhs, rhs } =>
+            state.clone().assign(lhs, rhs).unwrap(),
+        TNode::Assert { cond : BoolExpr::Compare { op, lhs, rhs, .. } } =>
+            state.clone().compare(lhs, op, rhs),
+        TNode::If { cond : BoolExpr::Compare { op, lhs, rhs, .. }, then, otherwise } => {
+            let then_state = exec(state.clone().compare(lhs, op, rhs), then, symbols);
+            let else_state = state.clone().compare(lhs, &op.negate(), rhs);
+            match otherwise {
+                Some(otherwise) => then_state.join(exec(else_state, otherwise, symbols)),
+                None => then_state.join(else_state),
+            }
+        },
+        _ => state,
+    }
+}
+
+/// runs a loop-free program on the domain `D` : blocks do not remove their variables,
+/// `assert(e1 cmp e2)` is interpreted as a guard, and `if`s must test a single comparison. Returns the final state and the
+/// symbols declared, by name.
+pub fn run<D : AbstractDomain>(src : &str) -> (D, HashMap<String, Symbol>) {
+    let program = parse_string(src).unwrap();
+    let mut symbols = HashMap::new();
+    let state = program.iter().fold(D::top(), |state, node| exec(state, node, &mut symbols));
+    (state, symbols)
+}
+
+/// value of the variable `var` at the end of the program `src`, run with `run`.
+pub fn value_of<D : AbstractDomain>(src : &str, var : &str) -> String {
+    let (mut state, symbols) = run::<D>(src);
+    state.print(symbols[var].clone())
+}
+
+/// runs the statements of the block `src` from `state`, whose variables
+/// must already contain the ones declared by the block.
+pub fn step<D : AbstractDomain>(state : D, src : &str) -> D {
+    let program = parse_string(src).unwrap();
+    let mut symbols = HashMap::new();
+    match &program[..] {
+        [TNode::Block { stmt, .. }] => stmt.iter().fold(state, |state, s| exec(state, s, &mut symbols)),
+        _ => panic!("a single block is expected"),
+    }
+}
@@ -2,4 +2,127 @@
  * author : Narcisse.
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
- */
\ No newline at end of file
+ */
+/// General trait for the abstraction of the values of a single variable.
+/// Lifted to environments by `NonRelational`, it only needs the lattice
+/// operators and the forward and backward semantics of the operators.
+use std::fmt;
+use crate::ast::*;
+
+pub trait ValueDomain : Clone + Eq + fmt::Display {
+    /// all the integers
+    fn top() -> Self;
+    /// empty set
+    fn bottom() -> Self;
+    /// abstraction of a single integer
+    fn constant(n : i64) -> Self;
+    /// abstraction of `rand(l, u)`, where l ranges in `lower` and u in `upper`
+    fn rand(lower : &Self, upper : &Self) -> Self;
+
+    /// wether an element represents the empty set
+    fn is_bottom(&self) -> bool;
+    /// wether an abstract element is included in another
+    fn subset(&self, rhs : &Self) -> bool;
+
+    /// abstract join
+    fn join(&self, rhs : &Self) -> Self;
+    /// abstract intersection
+    fn meet(&self, rhs : &Self) -> Self;
+    /// widening, defaults to the join for lattices of finite height
+    fn widen(&self, rhs : &Self) -> Self {
+        self.join(rhs)
+    }
+    /// widening stopping at the sorted `thresholds`, defaults to the widening
+    fn widen_thresholds(&self, rhs : &Self, _thresholds : &[i64]) -> Self {
+        self.widen(rhs)
+    }
+    /// narrowing, defaults to the meet for lattices of finite height
+    fn narrow(&self, rhs : &Self) -> Self {
+        self.meet(rhs)
+    }
+
+    /// forward semantics of an unary operator
+    fn unary(&self, op : IntUnaryOp) -> Self;
+    /// forward semantics of a binary operator
+    fn binary(&self, op : IntBinaryOp, rhs : &Self) -> Self;
+
+    /// backward semantics of a comparison : refines both operands
+    /// to the values that may satisfy `self cmp rhs`
+    fn compare(&self, cmp : &CompareOp, rhs : &Self) -> (Self, Self);
+
+    /// backward semantics of an unary operator : refines `self`
+    /// knowing that `op self` lies in `r`.
+    /// Both unary operators are involutions, hence the default.
+    fn bwd_unary(&self, op : IntUnaryOp, r : &Self) -> Self {
+        self.meet(&r.unary(op))
+    }
+
+    /// parts of an element that are strictly positive and strictly negative.
+    fn sign_split(&self) -> (Self, Self) {
+        let zero = Self::constant(0);
+        (self.compare(&CompareOp::GT, &zero).0, self.compare(&CompareOp::LT, &zero).0)
+    }
+
+    /// wether an element may represent the integer `n`.
+    fn may_be(&self, n : i64) -> bool {
+        !self.meet(&Self::constant(n)).is_bottom()
+    }
+
+    /// backward semantics of a binary operator : refines `self` and `rhs`
+    /// knowing that `self op rhs` lies in `r`. The divisors are never 0.
+    fn bwd_binary(&self, op : IntBinaryOp, rhs : &Self, r : &Self) -> (Self, Self) {
+        match op {
+            IntBinaryOp::Add =>
+                (self.meet(&r.binary(IntBinaryOp::Sub, rhs)),
+                rhs.meet(&r.binary(IntBinaryOp::Sub, self))),
+            IntBinaryOp::Sub =>
+                (self.meet(&r.binary(IntBinaryOp::Add, rhs)),
+                rhs.meet(&self.binary(IntBinaryOp::Sub, r))),
+            // x * y = r is an exact division of r, unless the product is 0 * y or x * 0
+            IntBinaryOp::Mul => {
+                let exact = |x : &Self, y : &Self| {
+                    if r.may_be(0) && y.may_be(0) { x.clone() } else { x.meet(&r.binary(IntBinaryOp::Div, y)) }
+                };
+                (exact(self, rhs), exact(rhs, self))
+            },
+            // x = r * y + m, where the remainder m has the sign of x and |m| < |y|
+            IntBinaryOp::Div => {
+                let (pos, neg) = rhs.sign_split();
+                let divisor = pos.join(&neg.unary(IntUnaryOp::SubUnary));
+                let m = divisor.binary(IntBinaryOp::Sub, &Self::constant(1));
+                let zero = Self::constant(0);
+                let product = r.binary(IntBinaryOp::Mul, &pos.join(&neg));
+                // the sign of x is first bounded with a remainder of any sign
+                let x = self.meet(&product.binary(IntBinaryOp::Add, &Self::rand(&m.unary(IntUnaryOp::SubUnary), &m)));
+                let (x_pos, x_neg) = x.sign_split();
+                let mut remainder = Self::bottom();
+                if !x_pos.is_bottom() || x.may_be(0) {
+                    remainder = remainder.join(&Self::rand(&zero, &m));
+                }
+                if !x_neg.is_bottom() {
+                    remainder = remainder.join(&Self::rand(&m.unary(IntUnaryOp::SubUnary), &zero));
+                }
+                (x.meet(&product.binary(IntBinaryOp::Add, &remainder)), pos.join(&neg))
+            },
+            // the remainder r has the sign of x, |r| < |y|, and x = r + q * y
+            IntBinaryOp::Mod => {
+                let (r_pos, r_neg) = r.sign_split();
+                let (x_pos, x_neg) = self.sign_split();
+                let mut x = if r.may_be(0) { self.clone() } else { Self::bottom() };
+                if !r_pos.is_bottom() {
+                    x = x.join(&x_pos);
+                }
+                if !r_neg.is_bottom() {
+                    x = x.join(&x_neg);
+                }
+                let (y_pos, y_neg) = rhs.sign_split();
+                let multiples = y_pos.join(&y_neg).binary(IntBinaryOp::Mul, &Self::top());
+                let x = x.meet(&r.binary(IntBinaryOp::Add, &multiples));
+                let r_abs = r_pos.join(&r_neg.unary(IntUnaryOp::SubUnary)).join(&r.meet(&Self::constant(0)));
+                let bound = Self::rand(&r_abs.binary(IntBinaryOp::Add, &Self::constant(1)), &Self::top());
+                let y = y_pos.meet(&bound).join(&y_neg.meet(&bound.unary(IntUnaryOp::SubUnary)));
+                (x, y)
+            },
+        }
+    }
+}
@@ -52,7 +52,7 @@ fn get_input(path: &PathBuf) -> Result<String, Box<dyn Error>> {
 /// failure otherwise.
 pub fn parse_file(
     filename: String
-) -> Result<Program, Box<(dyn std::error::Error + 'static)>> {
+) -> Result<Program, Box<dyn std::error::Error + 'static>> {
     let input_file = PathBuf::from(filename);
 
     match input_file.extension().and_then(OsStr::to_str) {
@@ -63,9 +63,16 @@ pub fn parse_file(
     }
 
     let input = get_input(&input_file)?;
+    parse_string(&input)
+}
 
+/// Function taking the source code of a program as argument and returning its AST,
+/// following the same conventions as `parse_file`.
+pub fn parse_string(
+    input: &str
+) -> Result<Program, Box<dyn std::error::Error + 'static>> {
     let lexerdef = lexer_l::lexerdef();
-    let lexer = lexerdef.lexer(&input);
+    let lexer = lexerdef.lexer(input);
 
     let p = RefCell::new(ParserState::default());
     let (res, errs) = parser_y::parse(&lexer, &p);
@@ -51,8 +51,8 @@ STMT -> Result<TNode, SemanticError>:
         { Ok(TNode::If { cond: $3?, then: Box::new($5?), otherwise: Some(Box::new($7?)) }) }
     | "WHILE" "(" BE ")" STMT      { Ok(TNode::While { cond: $3?, body: Box::new($5?) }) }
     | "ASSERT" "(" BE ")" ";"      { Ok(TNode::Assert { cond: $3? }) }
-    | "PRINT" "(" ID_LIST ")" ";"  { Ok(TNode::Print { vars: $3? }) }
-    | "HALT" ";"                   { Ok(TNode::Halt) }
+    | "PRINT" "(" ID_LIST ")" ";"  { Ok(TNode::Print { span: $span, vars: $3? }) }
+    | "HALT" ";"                   { Ok(TNode::Halt { span: $span }) }
     ;
 
 STMT_LIST -> Result<Vec<TNode>, SemanticError>:
@@ -85,7 +85,17 @@ ID_LIST -> Result<Vec<Symbol>, SemanticError>:
 
 // blocks
 BLOCK -> Result<TNode, SemanticError>:
-    "{" DECL_LIST STMT_LIST "}"  { Ok(TNode::Block { decl : $2?, stmt : $3? }) }
+    BLOCK_START DECL_LIST STMT_LIST "}"
+        {
+            p.borrow_mut().sym_table.exit_scope();
+            $1?;
+            Ok(TNode::Block { span : $span, decl : $2?, stmt : $3? })
+        }
+    ;
+
+// the declarations of a block are kept in a new scope
+BLOCK_START -> Result<(), SemanticError>:
+    "{"                          { p.borrow_mut().sym_table.enter_scope(); Ok(()) }
     ;
 
 DECL -> Result<Symbol, SemanticError>:
@@ -6,20 +6,12 @@
 use crate::symbol::{SymbolTable, Symbol};
 use crate::typing::TypeTable;
 
+#[derive(Default)]
 pub struct ParserState {
     pub sym_table: SymbolTable, // table of symbols
     pub type_table: TypeTable, // table for userdef types (upcoming)
 }
 
-impl Default for ParserState {
-    fn default() -> Self {
-        ParserState {
-            sym_table: SymbolTable::default(),
-            type_table: TypeTable::default(),
-        }
-    }
-}
-
 impl ParserState {
     pub fn get_var(&self, name: &str) -> Result<Symbol, String> {
         self.sym_table
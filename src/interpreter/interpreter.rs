@@ -3,15 +3,28 @@
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
  */
-use crate::domains::domain::AbstractDomain;
+use crate::domains::domain::{AbstractDomain, Init};
+use crate::domains::initialization::Initialization;
+use crate::domains::disjonctive::MAX_DISJUNCTS;
 use crate::ast::*;
 
+use lrpar::Span;
+use crate::symbol::Symbol;
+use crate::typing::Type;
+
+use super::report::{AnalysisReport, NodeId};
+
 /// types of errors the analysis can raise : it informally represents the properties of
 /// interest we want to study on the analysis.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AnalysisError {
     DeadCode,
-    FailedAssert,
+    AssertMayFail,
+    AssertAlwaysFails,
+    DivisionByZero,
+    Overflow,
+    MayBeUninitialized,
+    Uninitialized,
     UnknownVariable,
     IllegalOperation,
 }
@@ -19,8 +32,13 @@ pub enum AnalysisError {
 impl std::fmt::Display for AnalysisError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            Self::DeadCode => { write!(f, "DeadCode") },
-            Self::FailedAssert => { write!(f, "FailedAssert") },
+            Self::DeadCode => { write!(f, "unreachable statement") },
+            Self::AssertMayFail => { write!(f, "assertion may fail") },
+            Self::AssertAlwaysFails => { write!(f, "assertion always fails") },
+            Self::DivisionByZero => { write!(f, "division by zero") },
+            Self::Overflow => { write!(f, "integer overflow") },
+            Self::MayBeUninitialized => { write!(f, "read of a variable which may be uninitialized") },
+            Self::Uninitialized => { write!(f, "read of an uninitialized variable") },
             Self::UnknownVariable => { write!(f, "UnknownVariable") }
             Self::IllegalOperation => { write!(f, "IllegalOperation") }
         }
@@ -29,218 +47,927 @@ impl std::fmt::Display for AnalysisError {
 
 impl std::error::Error for AnalysisError {}
 
-/// structure for the results of the analysis we want to pretty-print.
-#[derive(Clone)]
-pub struct AnalysisResults {
-    msg : String,
-    node : TNode,
+/// helper function to interpret boolean expressions and prune the parts of the domain
+/// that are not satisfying the condition. The condition is put in negation normal form
+/// first, so that the comparisons are refined whatever the expected truth value.
+pub(crate) fn eval_boolexpr<D : AbstractDomain>(ctx : &mut D, be : &BoolExpr, should_satisfy : bool) -> D {
+    filter(ctx.clone(), &be.nnf(should_satisfy))
 }
 
-impl AnalysisResults {
-    /// constructor for an Analysis Result.
-    pub fn new(msg : String, node : TNode) -> Self {
-        AnalysisResults {
-            msg,
-            node,
+/// states of `ctx` where the condition `be` has the value `should_satisfy`, refined
+/// by at most `iterations` passes of the guard (local iterations, Granger 1992) :
+/// each pass may use the bounds found by the previous one on other operands.
+pub(crate) fn refine<D : AbstractDomain>(mut ctx : D, be : &BoolExpr, should_satisfy : bool, iterations : u32) -> D {
+    ctx = eval_boolexpr(&mut ctx, be, should_satisfy);
+    for _ in 1..iterations {
+        let next = eval_boolexpr(&mut ctx, be, should_satisfy);
+        if ctx.subset(&next) {
+            break;
         }
+        ctx = next;
     }
-
-    /// pretty printing the analysis result.
-    pub fn show(&mut self) {
-        println!("{} in statement :", self.msg);
-        display_tnode(&self.node, 0);
-    }
-}
-
-/// function implementing a basic widening to attain a fixpoint.
-fn fixpoint<D : AbstractDomain, T : FnMut(&mut D, &BoolExpr) -> Result<D, AnalysisError>>
-(mut f : T, x : &mut D, cond : &BoolExpr) -> Result<D, AnalysisError> {
-    let fx = f(x, cond)?;
-    if fx.subset(&x) {
-        Ok(fx)
-    }
-    else {
-        fixpoint(f, &mut fx.join(x.clone()), cond)
-    }
+    ctx
 }
 
-/// helper function to interpret boolean expressions and prune the parts of the domain
-/// that are not satisfying the condition.
-fn eval_boolexpr<D : AbstractDomain>(ctx : &mut D, be : &BoolExpr, should_satisfy : bool) -> D {
+/// states of `ctx` satisfying the condition `be`, in negation normal form.
+fn filter<D : AbstractDomain>(mut ctx : D, be : &BoolExpr) -> D {
     match be {
-        BoolExpr::Unary { span : _, op, exp } => {
-            match op {
-                BoolUnaryOp::Not =>
-                    eval_boolexpr(ctx, exp, !should_satisfy)
-            }
+        BoolExpr::Unary { span : _, op : BoolUnaryOp::Not, exp } => {
+            filter(ctx, &exp.nnf(false))
         },
         BoolExpr::Binary { span : _, op, lhs, rhs } => {
-            let eval_lhs = eval_boolexpr(ctx, lhs, should_satisfy);
-            let eval_rhs = eval_boolexpr(ctx, rhs, should_satisfy);
             match op {
-                BoolBinaryOp::And => eval_lhs.meet(eval_rhs),
-                BoolBinaryOp::Or => eval_lhs.join(eval_rhs),
+                // the right operand is evaluated in the states satisfying the left one
+                BoolBinaryOp::And => {
+                    let state = filter(ctx, lhs);
+                    filter(state, rhs)
+                },
+                BoolBinaryOp::Or => filter(ctx.clone(), lhs).join(filter(ctx, rhs)),
             }
         },
         BoolExpr::Compare { span : _, op, lhs, rhs } => {
             ctx.compare(lhs, op, rhs)
         },
         BoolExpr::Const { span : _, cst } => {
-            if *cst && should_satisfy {
-                ctx.clone()
+            if *cst { ctx } else { D::bottom() }
+        },
+    }
+}
+
+/// states of `ctx` where no division or remainder of `e` has a zero divisor :
+/// the spans of the ones whose divisor may be zero are pushed to `zeros`.
+pub(crate) fn nonzero_divisors<D : AbstractDomain>(e : &IntExpr, ctx : D, zeros : &mut Vec<Span>) -> D {
+    match e {
+        IntExpr::Unary { span : _, op : _, exp } =>
+            nonzero_divisors(exp, ctx, zeros),
+        IntExpr::Binary { span, op, lhs, rhs } => {
+            let ctx = nonzero_divisors(lhs, ctx, zeros);
+            let mut ctx = nonzero_divisors(rhs, ctx, zeros);
+            if let IntBinaryOp::Div | IntBinaryOp::Mod = op {
+                let zero = constant(*span, 0);
+                if !ctx.compare(rhs, &CompareOp::EQ, &zero).is_bottom() {
+                    zeros.push(*span);
+                    return ctx.compare(rhs, &CompareOp::NE, &zero);
+                }
             }
-            else {
-                D::bottom()
+            ctx
+        },
+        IntExpr::Rand { span : _, lower, upper } => {
+            let ctx = nonzero_divisors(lower, ctx, zeros);
+            nonzero_divisors(upper, ctx, zeros)
+        },
+        IntExpr::Ident { .. } | IntExpr::Const { .. } => ctx,
+    }
+}
+
+/// same as `nonzero_divisors` for the condition `be` in negation normal form, whose
+/// comparisons are checked in the states they are evaluated in : the ones satisfying
+/// the left operand of a conjunction, and the ones not satisfying the left operand
+/// of a disjunction. Returns the states where `be` holds.
+pub(crate) fn cond_nonzero_divisors<D : AbstractDomain>(be : &BoolExpr, ctx : D, zeros : &mut Vec<Span>) -> D {
+    match be {
+        BoolExpr::Unary { span : _, op : BoolUnaryOp::Not, exp } =>
+            cond_nonzero_divisors(&exp.nnf(false), ctx, zeros),
+        BoolExpr::Binary { span : _, op, lhs, rhs } => {
+            match op {
+                BoolBinaryOp::And => {
+                    let state = cond_nonzero_divisors(lhs, ctx, zeros);
+                    cond_nonzero_divisors(rhs, state, zeros)
+                },
+                BoolBinaryOp::Or => {
+                    let otherwise = cond_nonzero_divisors(&lhs.nnf(false), ctx.clone(), zeros);
+                    cond_nonzero_divisors(lhs, ctx, zeros).join(cond_nonzero_divisors(rhs, otherwise, zeros))
+                },
             }
         },
+        BoolExpr::Compare { span : _, op, lhs, rhs } => {
+            let ctx = nonzero_divisors(lhs, ctx, zeros);
+            let mut ctx = nonzero_divisors(rhs, ctx, zeros);
+            ctx.compare(lhs, op, rhs)
+        },
+        BoolExpr::Const { .. } => filter(ctx, be),
+    }
+}
+
+/// semantics of the type int.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntSemantics {
+    /// mathematical integers, which never overflow.
+    Unbounded,
+    /// 32-bit integers, the results out of range wrapping around.
+    Wrap,
+    /// 32-bit integers, the results out of range raising an alarm,
+    /// and being truncated to 32 bits.
+    Error,
+}
+
+/// bounds of the 32-bit integers.
+const INT_MIN : i64 = i32::MIN as i64;
+const INT_MAX : i64 = i32::MAX as i64;
+/// number of wrap-arounds of a value tracked precisely in both directions.
+const WRAPS : i64 = 2;
+
+/// constant expression of value `n`.
+fn constant(span : Span, n : i64) -> IntExpr {
+    IntExpr::Const { span, cst : n.to_string() }
+}
+
+/// brings the values of the variable `v` back into the range of 32-bit integers,
+/// by slices of width 2^32 when they only wrap around a few times.
+fn wrap<D : AbstractDomain>(v : &Symbol, span : Span, ctx : D) -> Result<D, AnalysisError> {
+    let x = IntExpr::Ident { span, var : v.clone() };
+    let width = 1i64 << 32;
+    let mut res = D::bottom();
+    for k in -WRAPS..=WRAPS {
+        let mut slice = ctx.clone()
+            .compare(&x, &CompareOp::GE, &constant(span, INT_MIN + k * width))
+            .compare(&x, &CompareOp::LE, &constant(span, INT_MAX + k * width));
+        if !slice.is_bottom() {
+            let shifted = IntExpr::Binary {
+                span,
+                op : IntBinaryOp::Sub,
+                lhs : Box::new(x.clone()),
+                rhs : Box::new(constant(span, k * width)),
+            };
+            res = res.join(slice.assign(v, &shifted)?);
+        }
+    }
+    let mut rest = ctx.clone().compare(&x, &CompareOp::LT, &constant(span, INT_MIN - WRAPS * width))
+        .join(ctx.clone().compare(&x, &CompareOp::GT, &constant(span, INT_MAX + WRAPS * width)));
+    if !rest.is_bottom() {
+        let range = IntExpr::Rand {
+            span,
+            lower : Box::new(constant(span, INT_MIN)),
+            upper : Box::new(constant(span, INT_MAX)),
+        };
+        res = res.join(rest.assign(v, &range)?);
     }
+    Ok(res)
+}
+
+/// how the value of an expression computed on mathematical integers
+/// relates to its value on wrapping 32-bit integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Wrapping {
+    /// both are equal.
+    Exact,
+    /// they are equal modulo 2^32.
+    Modular,
+    /// they are unrelated.
+    Unknown,
+}
+
+/// parameters of the analysis chosen on the command line.
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    /// number of loop iterations unrolled before looking for a fixpoint.
+    pub unroll : u32,
+    /// number of loop iterations using joins before switching to widenings.
+    pub delay : u32,
+    /// number of decreasing iterations once a post-fixpoint is reached.
+    pub narrow : u32,
+    /// whether the iteration counts of the loops are traced.
+    pub debug : bool,
+    /// semantics of the type int.
+    pub integers : IntSemantics,
+    /// maximal number of passes refining the state by a condition.
+    pub local_iter : u32,
+    /// thresholds of the widenings, besides the ones found in the program.
+    pub thresholds : Vec<i64>,
+    /// bound on the number of disjuncts of the disjunctive domains.
+    pub disjuncts : usize,
+}
+
+impl Default for AnalysisOptions {
+    // a few joins before widening keep the bounds which stabilize quickly
+    fn default() -> Self {
+        AnalysisOptions {
+            unroll : 0,
+            delay : 3,
+            narrow : 2,
+            debug : false,
+            integers : IntSemantics::Unbounded,
+            local_iter : 1,
+            thresholds : Vec::new(),
+            disjuncts : MAX_DISJUNCTS,
+        }
+    }
+}
+
+/// thresholds of the widenings : the integer literals `constants` of the program
+/// and their neighbours, which bound the variables compared to them, with the
+/// `extra` ones. They are sorted.
+pub(crate) fn thresholds(constants : Vec<i64>, extra : &[i64]) -> Vec<i64> {
+    let mut res : Vec<i64> = constants
+        .into_iter()
+        .flat_map(|c| [c.saturating_sub(1), c, c.saturating_add(1)])
+        .chain(extra.iter().copied())
+        .collect();
+    res.sort_unstable();
+    res.dedup();
+    res
 }
 
 /// structure for the analyzer.
 pub struct MonotonicFixpointIterator<D : AbstractDomain> {
     base : D,
     next_nodes : Vec<TNode>,
-    unroll : u32,
+    options : AnalysisOptions,
+    /// sorted thresholds of the widenings.
+    thresholds : Vec<i64>,
+    report : AnalysisReport<D>,
+    /// innermost statement where the pending error was raised.
+    origin : Option<NodeId>,
 }
 
 impl<D> MonotonicFixpointIterator<D>
 where D : AbstractDomain {
-    /// constructor for a new analyzer : it should precise an unrolling bound 
-    /// and the program ast.
-    pub fn new(next_nodes : Program, unroll : u32) -> Self {
+    /// constructor for a new analyzer : it should precise the program ast
+    /// and the iteration parameters (unrolling bound and widening delay).
+    pub fn new(next_nodes : Program, options : AnalysisOptions) -> Self {
+        let size = next_nodes.iter().map(TNode::size).sum();
+        let thresholds = thresholds(constants(&next_nodes), &options.thresholds);
         Self {
-            base : D::bottom(),
+            base : D::initial_state(&options),
             next_nodes,
-            unroll,
+            options,
+            thresholds,
+            report : AnalysisReport::new(size),
+            origin : None,
         }
     }
 
-    /// inner function to pretty print the results of the analysis.
-    fn show_results(&mut self, msgs : Vec<AnalysisResults>) -> () {
-        for msg in msgs {
-            msg.clone().show();
+    /// function to evaluate the statement of identifier `id` according to a
+    /// context `ctx`, recording its states in the report.
+    fn eval_stmt(&mut self, stmt : &TNode, id : NodeId, ctx : &mut D) -> Result<D, AnalysisError> {
+        self.report.record_before(id, ctx);
+        let res = self.transfer(stmt, id, ctx);
+        match &res {
+            Ok(state) => self.report.record_after(id, state),
+            Err(_) => { self.origin.get_or_insert(id); },
         }
+        res
     }
 
-    /// function to evaluate a statement according to a context `ctx`.
-    fn eval_stmt(&mut self, stmt : &TNode, ctx : &mut D) -> Result<D, AnalysisError> {
-        match stmt {
-            TNode::Assert { cond } => {
-                let res = eval_boolexpr(ctx, &cond, true);
-                if res.is_bottom() {
-                    Err(AnalysisError::FailedAssert)
+    /// raises an alarm when the variable `v`, read at `span`, may be uninitialized : its
+    /// value is then the top one, and no execution goes on when it is uninitialized.
+    fn check_read(&mut self, v : &Symbol, span : Span, id : NodeId, ctx : D) -> D {
+        match ctx.initialization(v) {
+            Init::Initialized => ctx,
+            Init::MaybeUninitialized => {
+                self.report.push_alarm(AnalysisError::MayBeUninitialized, id, Some(span));
+                ctx
+            },
+            Init::Uninitialized => {
+                self.report.push_alarm(AnalysisError::Uninitialized, id, Some(span));
+                D::bottom()
+            },
+        }
+    }
+
+    /// same as `check_read` for the variables read by `e`.
+    fn check_reads(&mut self, e : &IntExpr, id : NodeId, ctx : D) -> D {
+        match e {
+            IntExpr::Ident { span, var } => self.check_read(var, *span, id, ctx),
+            IntExpr::Const { .. } => ctx,
+            IntExpr::Unary { span : _, op : _, exp } => self.check_reads(exp, id, ctx),
+            IntExpr::Binary { span : _, op : _, lhs, rhs } => {
+                let ctx = self.check_reads(lhs, id, ctx);
+                self.check_reads(rhs, id, ctx)
+            },
+            IntExpr::Rand { span : _, lower, upper } => {
+                let ctx = self.check_reads(lower, id, ctx);
+                self.check_reads(upper, id, ctx)
+            },
+        }
+    }
+
+    /// raises an alarm for each division or remainder of `e` whose divisor may be zero,
+    /// and keeps the states where it is not, as the other ones stop the execution.
+    fn check_divisions(&mut self, e : &IntExpr, id : NodeId, ctx : D) -> D {
+        let mut zeros = Vec::new();
+        let ctx = nonzero_divisors(e, ctx, &mut zeros);
+        for span in zeros {
+            self.report.push_alarm(AnalysisError::DivisionByZero, id, Some(span));
+        }
+        ctx
+    }
+
+    /// relation between the value of `e` computed by the domain on unbounded integers and
+    /// its value on 32-bit integers. With the error semantics, an alarm is raised for each
+    /// literal and arithmetic operation of `e` whose value may leave the range of 32-bit integers.
+    fn check_overflows(&mut self, e : &IntExpr, id : NodeId, ctx : &mut D) -> Wrapping {
+        if self.options.integers == IntSemantics::Unbounded {
+            return Wrapping::Exact;
+        }
+        let wrapping = match e {
+            IntExpr::Ident { .. } => return Wrapping::Exact,
+            // the literals and their negations are only checked as a whole
+            IntExpr::Const { .. } => Wrapping::Exact,
+            IntExpr::Unary { span : _, op : IntUnaryOp::SubUnary, exp } if matches!(**exp, IntExpr::Const { .. }) =>
+                Wrapping::Exact,
+            IntExpr::Rand { span : _, lower, upper } => {
+                let w = self.check_overflows(lower, id, ctx).max(self.check_overflows(upper, id, ctx));
+                // the bounds are no longer the ones of the domain
+                return if w == Wrapping::Exact { Wrapping::Exact } else { Wrapping::Unknown };
+            },
+            IntExpr::Unary { span : _, op : _, exp } =>
+                self.check_overflows(exp, id, ctx),
+            IntExpr::Binary { span : _, op, lhs, rhs } => {
+                let w = self.check_overflows(lhs, id, ctx).max(self.check_overflows(rhs, id, ctx));
+                match op {
+                    // the divisions do not commute with the wrap-arounds
+                    IntBinaryOp::Div | IntBinaryOp::Mod if w != Wrapping::Exact => Wrapping::Unknown,
+                    _ => w,
                 }
-                else {
-                    Ok(res)
+            },
+        };
+        let span = *e.get_span();
+        let out = ctx.compare(e, &CompareOp::LT, &constant(span, INT_MIN))
+            .join(ctx.compare(e, &CompareOp::GT, &constant(span, INT_MAX)));
+        if out.is_bottom() {
+            return wrapping;
+        }
+        if self.options.integers == IntSemantics::Error {
+            self.report.push_alarm(AnalysisError::Overflow, id, Some(span));
+        }
+        wrapping.max(Wrapping::Modular)
+    }
+
+    /// checks the expressions of the condition `cond`, and returns the state
+    /// in which it is evaluated with the value `should_satisfy`, refined by
+    /// at most `local_iter` passes of `guard`.
+    fn eval_cond(&mut self, cond : &BoolExpr, should_satisfy : bool, id : NodeId, ctx : D) -> D {
+        let cond = cond.nnf(should_satisfy);
+        let mut ctx = self.guard(&cond, id, ctx);
+        for _ in 1..self.options.local_iter {
+            let next = self.guard(&cond, id, ctx.clone());
+            if ctx.subset(&next) {
+                break;
+            }
+            ctx = next;
+        }
+        ctx
+    }
+
+    /// states of `ctx` satisfying the condition `be` in negation normal form, as `filter`,
+    /// where the reads, divisions and overflows of each comparison are checked in the states it is
+    /// evaluated in : the ones satisfying the left operand of a conjunction, and the ones
+    /// not satisfying the left operand of a disjunction.
+    fn guard(&mut self, be : &BoolExpr, id : NodeId, ctx : D) -> D {
+        match be {
+            BoolExpr::Unary { span : _, op : BoolUnaryOp::Not, exp } =>
+                self.guard(&exp.nnf(false), id, ctx),
+            BoolExpr::Binary { span : _, op, lhs, rhs } => {
+                match op {
+                    BoolBinaryOp::And => {
+                        let state = self.guard(lhs, id, ctx);
+                        self.guard(rhs, id, state)
+                    },
+                    BoolBinaryOp::Or => {
+                        let otherwise = self.guard(&lhs.nnf(false), id, ctx.clone());
+                        self.guard(lhs, id, ctx).join(self.guard(rhs, id, otherwise))
+                    },
                 }
             },
+            BoolExpr::Compare { span : _, op, lhs, rhs } => {
+                let ctx = self.check_reads(lhs, id, ctx);
+                let ctx = self.check_reads(rhs, id, ctx);
+                let ctx = self.check_divisions(lhs, id, ctx);
+                let ctx = self.check_divisions(rhs, id, ctx);
+                self.wrap_compare(lhs, op, rhs, id, ctx.clone()).unwrap_or(ctx)
+            },
+            BoolExpr::Const { span : _, cst } => {
+                if *cst { ctx } else { D::bottom() }
+            },
+        }
+    }
+
+    /// comparison of `lhs` and `rhs` : when the value of a compared expression on 32-bit
+    /// integers may not be the computed one, the comparison is evaluated on a temporary
+    /// holding its 32-bit value.
+    fn wrap_compare(&mut self, lhs : &IntExpr, op : &CompareOp, rhs : &IntExpr, id : NodeId, ctx : D) -> Result<D, AnalysisError> {
+        let mut temps = Vec::new();
+        let (lhs, ctx) = self.wrap_operand(lhs, id, ctx, &mut temps)?;
+        let (rhs, mut ctx) = self.wrap_operand(rhs, id, ctx, &mut temps)?;
+        let state = ctx.compare(&lhs, op, &rhs);
+        Ok(temps.iter().fold(state, |mut state, t| state.remove_variable(t)))
+    }
+
+    /// compared expression `e`, or a new variable of `temps` holding its 32-bit value
+    /// in the returned state when it may differ from the computed one.
+    fn wrap_operand(&mut self, e : &IntExpr, id : NodeId, mut ctx : D, temps : &mut Vec<Symbol>) -> Result<(IntExpr, D), AnalysisError> {
+        let wrapping = self.check_overflows(e, id, &mut ctx);
+        if wrapping == Wrapping::Exact {
+            return Ok((e.clone(), ctx));
+        }
+        // the name of a temporary is not a C identifier
+        let t = Symbol::Variable { name : format!("#{}", temps.len()), dtype : Type::Int, depth : 0 };
+        temps.push(t.clone());
+        let ctx = self.assign_wrapped(&t, e, wrapping, ctx.add_variable(&t))?;
+        Ok((IntExpr::Ident { span : *e.get_span(), var : t }, ctx))
+    }
+
+    /// assignment of the 32-bit value of `e` to `v`, knowing how it relates to the computed one.
+    fn assign_wrapped(&self, v : &Symbol, e : &IntExpr, wrapping : Wrapping, mut ctx : D) -> Result<D, AnalysisError> {
+        let span = *e.get_span();
+        match wrapping {
+            Wrapping::Exact => ctx.assign(v, e),
+            Wrapping::Modular => wrap(v, span, ctx.assign(v, e)?),
+            Wrapping::Unknown => {
+                let range = IntExpr::Rand {
+                    span,
+                    lower : Box::new(constant(span, INT_MIN)),
+                    upper : Box::new(constant(span, INT_MAX)),
+                };
+                ctx.assign(v, &range)
+            },
+        }
+    }
+
+    /// with 32-bit integers, the value of a new variable `v` is in their range.
+    fn declare(&self, v : &Symbol, mut ctx : D) -> D {
+        if self.options.integers == IntSemantics::Unbounded {
+            return ctx;
+        }
+        let span = Span::new(0, 0);
+        let x = IntExpr::Ident { span, var : v.clone() };
+        ctx.compare(&x, &CompareOp::GE, &constant(span, INT_MIN))
+            .compare(&x, &CompareOp::LE, &constant(span, INT_MAX))
+    }
+
+    /// assignment of `e` to the variable `v` : with 32-bit integers, the values
+    /// out of range are brought back into it.
+    fn eval_assign(&mut self, v : &Symbol, e : &IntExpr, id : NodeId, ctx : D) -> Result<D, AnalysisError> {
+        let ctx = self.check_reads(e, id, ctx);
+        let mut ctx = self.check_divisions(e, id, ctx);
+        let wrapping = self.check_overflows(e, id, &mut ctx);
+        self.assign_wrapped(v, e, wrapping, ctx)
+    }
+
+    /// state reached by the statement `stmt` from the context `ctx`.
+    fn transfer(&mut self, stmt : &TNode, id : NodeId, ctx : &mut D) -> Result<D, AnalysisError> {
+        match stmt {
+            TNode::Assert { cond } => {
+                // the alarms are raised from the recorded outcomes once the analysis
+                // ends, and the analysis goes on where the assertion holds
+                let holds = self.eval_cond(cond, true, id, ctx.clone());
+                let fails = self.eval_cond(cond, false, id, ctx.clone());
+                self.report.record_assert(id, &holds, &fails);
+                Ok(holds)
+            },
             TNode::Assign { lhs, rhs } => {
-                ctx.assign(&lhs, rhs)
+                self.eval_assign(lhs, rhs, id, ctx.clone())
             },  
-            TNode::Block { decl, stmt } => {
-                let new_ctx = ctx;
-                let _ = decl
-                    .iter()
-                    .map(|x| new_ctx.add_variable(x));
-                /* we create a new context holding the new scope
-                so that when we leave this block, we will keep our old context */
-                self.eval_stmt_list(&mut stmt.clone(), new_ctx)
-            },
-            TNode::Halt => {
-                Ok(ctx.clone())
+            TNode::Block { decl, stmt, .. } => {
+                // the variables declared by the block only live inside of it
+                let mut state = decl.iter().fold(ctx.clone(), |mut state, x| {
+                    let state = state.add_variable(x);
+                    self.declare(x, state)
+                });
+                let state = self.eval_stmt_list(stmt, id + 1, &mut state)?;
+                Ok(decl.iter().fold(state, |mut state, x| state.remove_variable(x)))
+            },
+            TNode::Halt { .. } => {
+                Ok(D::bottom())
             },
             TNode::If { cond, then, otherwise } => {
                 let mut then_domain =
-                    eval_boolexpr(ctx, &cond, true);
+                    self.eval_cond(cond, true, id, ctx.clone());
+                let mut else_domain =
+                    self.eval_cond(cond, false, id, ctx.clone());
+                let then_res = self.eval_stmt(then, id + 1, &mut then_domain)?;
                 if let Some(otherwise) = otherwise {
-                    let mut else_domain =
-                        eval_boolexpr(ctx, &cond, false);
                     Ok(D::join(
-                        self.eval_stmt(then, &mut then_domain)?,
-                        self.eval_stmt(otherwise, &mut else_domain)?
+                        then_res,
+                        self.eval_stmt(otherwise, id + 1 + then.size(), &mut else_domain)?
                     ))
                 }
                 else {
-                    Ok(then_domain)
+                    // without else branch, the states falsifying the condition go through
+                    Ok(D::join(then_res, else_domain))
                 }
-
             },
-            TNode::Print { vars } => {
-                let fmt = vars
-                    .iter()
-                    .map(|x| -> String { 
-                        format!("{} : {}", x.get_name(), ctx.print(x.clone()))
-                    })
-                    .fold(String::from(""),
-                        |acc, x| -> String { format!("{}, {}", acc, x) });
-                println!("{}", fmt);
-                Ok(ctx.clone())
+            TNode::Print { span, vars } => {
+                // the values are read from the recorded states once the analysis ends
+                Ok(vars.iter().fold(ctx.clone(), |state, v| self.check_read(v, *span, id, state)))
             },
             TNode::While { cond, body } => {
-                let mut in_loop =
-                    eval_boolexpr(ctx,&cond, true);
-                for _ in 1..self.unroll {
-                    in_loop = D::join(
-                        in_loop.clone(),
-                        self.eval_stmt(body, &mut in_loop)?
-                    );
+                // the unrolled iterations are executed separately, and
+                // the loop may be left at each of their heads
+                let mut head = ctx.clone();
+                let mut exits = D::bottom();
+                for _ in 0..self.options.unroll {
+                    exits = exits.join(head.clone());
+                    head = self.eval_body(cond, body, id, head)?;
                 }
-                in_loop = fixpoint(
-                    |x, cond| -> Result<D, AnalysisError> {
-                        let mut filtered_comp =
-                            eval_boolexpr(x, cond, true);
-                        let result =
-                            self.eval_stmt(body, &mut filtered_comp)?;
-                        Ok(D::widen(x.clone(), result))
-                    },
-                    &mut in_loop,
-                    &cond
-                )?;
-                Ok(in_loop)
+                let inv = self.loop_invariant(cond, body, id, head)?;
+                // the condition is evaluated at every head of the loop, which
+                // is left when it does not hold
+                Ok(self.eval_cond(cond, false, id, exits.join(inv)))
             },
         }
     }
 
-    /// helper function for the evaluation of a statement vector.
-    /// It basically is a fold using `eval_stmt`.
-    fn eval_stmt_list(&mut self, stmt_list : &mut Vec<TNode>, ctx : &mut D) -> Result<D, AnalysisError> {
-        self.base = ctx.clone();
-        while !stmt_list.is_empty() {
-            let stmt = &stmt_list.pop().unwrap();
-            match &stmt {
-                TNode::Halt => {
-                    if !stmt_list.is_empty() {
-                        return Err(AnalysisError::DeadCode);
-                    }
-                },
-                _ => (),
+    /// state at the end of the body of the loop `while (cond) body`, of identifier
+    /// `id`, entered from the state `head`.
+    fn eval_body(&mut self, cond : &BoolExpr, body : &TNode, id : NodeId, head : D) -> Result<D, AnalysisError> {
+        let mut state = self.eval_cond(cond, true, id, head);
+        self.eval_stmt(body, id + 1, &mut state)
+    }
+
+    /// invariant of the head of the loop `while (cond) body`, of identifier `id`, entered with `entry` :
+    /// increasing iterations joining `delay` times then widening with thresholds until a post-fixpoint
+    /// is reached, followed by at most `narrow` decreasing iterations. The body is
+    /// evaluated once more from the invariant, and only this pass is reported.
+    fn loop_invariant(&mut self, cond : &BoolExpr, body : &TNode, id : NodeId, entry : D) -> Result<D, AnalysisError> {
+        let report = self.report.clone();
+        let mut inv = entry.clone();
+        let mut increasing : u32 = 0;
+        loop {
+            let next = entry.clone().join(
+                self.eval_body(cond, body, id, inv.clone())?
+            );
+            if next.subset(&inv) {
+                break;
             }
-            self.base = D::join(
-                self.base.clone(),
-                self.eval_stmt(stmt, &mut self.base.clone())?
-            )
-        }
-        Ok(self.base.clone())
-    }
-
-    /// main function of the analyzer : evaluating the program and showing the associated results.
-    pub fn eval_prog(&mut self) -> Result<(), AnalysisError> {
-        let mut res : Vec<AnalysisResults> = Vec::new();
-        for stmt in self.next_nodes.clone() {
-            let curr_res =
-                self.eval_stmt(&stmt, &mut self.base.clone());
-            if let Err(e) = curr_res {
-                res.push(AnalysisResults::new(
-                    e.to_string(),
-                    stmt,
-                ));
+            inv =
+                if increasing < self.options.delay {
+                    inv.join(next)
+                }
+                else {
+                    inv.widen_thresholds(next, &self.thresholds)
+                };
+            increasing += 1;
+        }
+        let mut decreasing : u32 = 0;
+        while decreasing < self.options.narrow {
+            let next = entry.clone().join(
+                self.eval_body(cond, body, id, inv.clone())?
+            );
+            if inv.subset(&next) {
+                break;
+            }
+            inv = inv.narrow(next);
+            decreasing += 1;
+        }
+        if self.options.debug {
+            eprintln!("loop head : {} increasing iterations ({} joins), {} decreasing iterations",
+                increasing, increasing.min(self.options.delay), decreasing);
+        }
+        self.report = report;
+        self.eval_body(cond, body, id, inv.clone())?;
+        Ok(inv)
+    }
+
+    /// helper function for the evaluation of a statement vector : the statements
+    /// run in order, each one from the state left by the previous one. The first
+    /// one has the identifier `id`.
+    fn eval_stmt_list(&mut self, stmt_list : &[TNode], id : NodeId, ctx : &mut D) -> Result<D, AnalysisError> {
+        let mut state = ctx.clone();
+        let mut id = id;
+        for stmt in stmt_list {
+            state = self.eval_stmt(stmt, id, &mut state)?;
+            id += stmt.size();
+        }
+        Ok(state)
+    }
+
+    /// main function of the analyzer : evaluating the program and returning the associated results.
+    pub fn eval_prog(&mut self) -> Result<AnalysisReport<D>, AnalysisError> {
+        let program = self.next_nodes.clone();
+        let mut id = 0;
+        for stmt in &program {
+            self.origin = None;
+            match self.eval_stmt(stmt, id, &mut self.base.clone()) {
+                Ok(state) => self.base = state,
                 // in that case, we leave self.base as it was to keep the analysis
+                Err(e) => self.report.push_alarm(e, self.origin.unwrap_or(id), None),
+            }
+            id += stmt.size();
+        }
+        self.report.collect_alarms(&program);
+        self.report.collect_unreachable(&program);
+        self.report.collect_prints(&program);
+        Ok(self.report.clone())
+    }
+}
+
+/// analysis driver : runs the analysis of `program`, parsed from the source `src`,
+/// inside the domain `D` tracking the initialization of the variables, and shows
+/// the values printed and the alarms raised.
+pub fn analyze<D : AbstractDomain>(program : Program, src : &str, options : AnalysisOptions) -> Result<(), AnalysisError> {
+    let report = MonotonicFixpointIterator::<Initialization<D>>::new(program.clone(), options).eval_prog()?;
+    report.show(&program, src);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::file_parser::parse_string;
+    use crate::domains::constant_domain::ConstantDomain;
+    use crate::domains::concrete_domain::ConcreteDomain;
+    use crate::domains::congruence_domain::CongruenceDomain;
+    use crate::domains::disjonctive::Disjunctive;
+    use crate::domains::interval_domain::IntervalDomain;
+    use crate::domains::octagon_domain::OctagonDomain;
+
+    /// analyzes the program `src`, and returns the values shown by its `print`s.
+    fn printed<D : AbstractDomain>(src : &str) -> Vec<String> {
+        let program = parse_string(src).unwrap();
+        let report = MonotonicFixpointIterator::<D>::new(program, AnalysisOptions::default()).eval_prog().unwrap();
+        report.prints()
+            .iter()
+            .flat_map(|p| p.values.iter().map(|(name, value)| format!("{} : {}", name, value)))
+            .collect()
+    }
+
+    /// evaluates the statements of the block `src`, whose variables are declared
+    /// beforehand, and returns the value of `var` in the final state.
+    fn value_after<D : AbstractDomain>(src : &str, var : &str) -> String {
+        let program = parse_string(src).unwrap();
+        let [TNode::Block { decl, stmt, .. }] = &program[..] else {
+            panic!("a single block is expected");
+        };
+        let mut iterator = MonotonicFixpointIterator::<D>::new(program.clone(), AnalysisOptions::default());
+        let mut state = decl.iter().fold(D::top(), |mut state, v| state.add_variable(v));
+        let mut state = iterator.eval_stmt_list(stmt, 1, &mut state).unwrap();
+        let v = decl.iter().find(|v| v.get_name() == var).unwrap();
+        state.print(v.clone())
+    }
+
+    #[test]
+    fn test_sequence() {
+        assert_eq!(value_after::<ConstantDomain>("{ int x; x = 1; x = x + 1; print(x); }", "x"), "2");
+        assert_eq!(value_after::<ConstantDomain>("{ int x; int y; x = 3; y = x; x = 5; }", "y"), "3");
+        assert_eq!(value_after::<IntervalDomain>("{ int x; x = rand(0,3); x = x * 2; x = x - 1; }", "x"), "[-1;5]");
+    }
+
+    /// evaluates the whole program `src` from the empty state.
+    fn analyze_string<D : AbstractDomain>(src : &str) -> Result<D, AnalysisError> {
+        let program = parse_string(src).unwrap();
+        let mut iterator = MonotonicFixpointIterator::<D>::new(program.clone(), AnalysisOptions::default());
+        let mut state = D::top();
+        let mut id = 0;
+        for s in &program {
+            state = iterator.eval_stmt(s, id, &mut state)?;
+            id += s.size();
+        }
+        Ok(state)
+    }
+
+    /// analyzes the program `src`, and returns its alarms with their positions.
+    fn alarms<D : AbstractDomain>(src : &str) -> Vec<(AnalysisError, Option<(usize, usize)>)> {
+        let program = parse_string(src).unwrap();
+        let report = MonotonicFixpointIterator::<D>::new(program, AnalysisOptions::default()).eval_prog().unwrap();
+        report.alarms().iter().map(|a| (a.error, a.position(src))).collect()
+    }
+
+    #[test]
+    fn test_scoping() {
+        let src = "{ int x; x = 1; { int x; x = 5; assert(x == 5); } assert(x == 1); }";
+        assert!(analyze_string::<IntervalDomain>(src).is_ok());
+        let src = "{ int x; x = 1; { int y; y = 2; x = x + y; } assert(x == 3); }";
+        assert!(analyze_string::<ConstantDomain>(src).is_ok());
+        let src = "{ int x; x = 1; { int x; x = 5; } assert(x == 5); }";
+        assert_eq!(alarms::<IntervalDomain>(src), [(AnalysisError::AssertAlwaysFails, Some((1, 42)))]);
+        // the variables are removed when leaving their block
+        let src = "{ int x; x = 1; }";
+        assert!(analyze_string::<OctagonDomain>(src).unwrap() == OctagonDomain::top());
+    }
+
+    #[test]
+    fn test_loop_exit() {
+        assert_eq!(printed::<IntervalDomain>(include_str!("../../test/0411_loop_exit.c")), ["x : [10;10]"]);
+        assert_eq!(printed::<IntervalDomain>(include_str!("../../test/0412_loop_exit_rand.c")), ["x : [21;23]"]);
+    }
+
+    #[test]
+    fn test_concrete_loops() {
+        // the loops which do not stabilize go to top, the others stay exact
+        assert_eq!(printed::<ConcreteDomain>(include_str!("../../test/0402_loop_infinite.c")), ["x : top"]);
+        assert_eq!(printed::<ConcreteDomain>(include_str!("../../test/0403_loop_nondet.c")), ["x : top"]);
+        assert_eq!(printed::<ConcreteDomain>(include_str!("../../test/0411_loop_exit.c")), ["x : {10}"]);
+    }
+
+    #[test]
+    fn test_loop_exit_relational() {
+        assert_eq!(printed::<OctagonDomain>(include_str!("../../test/0413_loop_exit_rel.c")), ["x : [0;50], x-N in [0;0]", "N : [0;50], N-x in [0;0]"]);
+    }
+
+    #[test]
+    fn test_disjunctive_loops() {
+        // the narrowings of the disjuncts keep the values of every iteration
+        let src = include_str!("../../test/0405_loop_rel.c");
+        assert_eq!(printed::<Disjunctive<IntervalDomain>>(src), ["x : [0;49]", "N : [1;50]"]);
+        assert_eq!(printed::<Disjunctive<OctagonDomain>>(src)[0], "x : [0;49], x-N in [-50;-1]");
+    }
+
+    #[test]
+    fn test_report() {
+        let src = "{ int x; x = 1; while (x < 5) { x = x + 1; print(x); } if (x > 2) x = 4; assert(x == 5); }";
+        let program = parse_string(src).unwrap();
+        let report = MonotonicFixpointIterator::<IntervalDomain>::new(program.clone(), AnalysisOptions::default()).eval_prog().unwrap();
+        let nodes = statements(&program);
+        assert_eq!(nodes.len(), 9);
+        assert!(matches!(nodes[7], TNode::Assign { .. }));
+        let x = match &program[0] {
+            TNode::Block { decl, .. } => decl[0].clone(),
+            _ => unreachable!(),
+        };
+        let value = |state : Option<&IntervalDomain>| state.unwrap().clone().print(x.clone());
+        // the states inside the loop are the ones of its invariant
+        assert_eq!(value(report.before(4)), "[1;4]");
+        assert_eq!(value(report.after(2)), "[5;5]");
+        assert_eq!(value(report.before(7)), "[5;5]");
+        assert_eq!(value(report.before(8)), "[4;4]");
+        assert!(report.after(8).unwrap().is_bottom());
+        assert_eq!(report.prints().len(), 1);
+        assert_eq!(report.prints()[0].node, 5);
+        assert_eq!(report.prints()[0].values, [("x".to_string(), "[2;5]".to_string())]);
+        assert_eq!(report.alarms().len(), 1);
+        assert_eq!(report.alarms()[0].error, AnalysisError::AssertAlwaysFails);
+        assert_eq!(report.alarms()[0].node, 8);
+    }
+
+    #[test]
+    fn test_assert() {
+        let src = "{ int x; x = rand(0,10);\n  assert(x > 5);\n  assert(x < 3);\n  assert(x > 0); }";
+        assert_eq!(alarms::<IntervalDomain>(src), [
+            (AnalysisError::AssertMayFail, Some((2, 10))),
+            (AnalysisError::AssertAlwaysFails, Some((3, 10))),
+            (AnalysisError::DeadCode, Some((4, 10))),
+        ]);
+        // the assertions of unreachable statements hold
+        let src = "{ int x; x = 1; if (x > 1) { assert(x < 0); } }";
+        assert_eq!(alarms::<IntervalDomain>(src), [(AnalysisError::DeadCode, Some((1, 28)))]);
+    }
+
+    #[test]
+    fn test_division() {
+        assert_eq!(alarms::<IntervalDomain>(include_str!("../../test/0031_div.c")), [(AnalysisError::DivisionByZero, Some((3, 7)))]);
+        assert!(alarms::<IntervalDomain>(include_str!("../../test/0029_div.c")).is_empty());
+        // the analysis goes on with the divisors different from zero
+        let src = "{ int x; int y; y = rand(0,5);\n  if (x % y > 0) x = 1;\n  assert(y > 0); }";
+        assert_eq!(alarms::<IntervalDomain>(src), [(AnalysisError::DivisionByZero, Some((2, 7)))]);
+        let src = "{ int x; x = 12 / rand(-1,1); print(x); }";
+        assert_eq!(printed::<IntervalDomain>(src), ["x : [-12;12]"]);
+        // the divisions are only checked when the left operand does not decide the condition
+        let src = "{ int x; x = rand(0,3);\n  if (x == 0 || 10 / x > 4) { print(x); } else { print(x); }\n  if (x != 0 && 10 / x > 4) { print(x); } }";
+        assert!(alarms::<IntervalDomain>(src).is_empty());
+        assert_eq!(printed::<ConcreteDomain>(src), ["x : {0, 1, 2}", "x : {3}", "x : {1, 2}"]);
+    }
+
+    #[test]
+    fn test_machine_integers() {
+        let src = "{ int x; x = 2147483647;\n  x = x + 1; print(x);\n  x = rand(0,10) * 1000; print(x); }";
+        let program = parse_string(src).unwrap();
+        for integers in [IntSemantics::Wrap, IntSemantics::Error] {
+            let options = AnalysisOptions { integers, ..AnalysisOptions::default() };
+            let report = MonotonicFixpointIterator::<IntervalDomain>::new(program.clone(), options).eval_prog().unwrap();
+            let values : Vec<&str> = report.prints().iter().map(|p| p.values[0].1.as_str()).collect();
+            assert_eq!(values, ["[-2147483648;-2147483648]", "[0;10000]"]);
+            let alarms : Vec<_> = report.alarms().iter().map(|a| (a.error, a.position(src))).collect();
+            if integers == IntSemantics::Error {
+                assert_eq!(alarms, [(AnalysisError::Overflow, Some((2, 7)))]);
+            }
+            else {
+                assert!(alarms.is_empty());
+            }
+        }
+        // the literals out of range wrap around, and the operations are checked
+        // even when the value of the whole expression is in range
+        let src = "{ int x; int y; x = 3000000000; print(x);\n  x = -2147483648; y = rand(0,3000000000);\n  x = 2147483647; y = x + 1 - 1; print(y); }";
+        let program = parse_string(src).unwrap();
+        for integers in [IntSemantics::Wrap, IntSemantics::Error] {
+            let options = AnalysisOptions { integers, ..AnalysisOptions::default() };
+            let report = MonotonicFixpointIterator::<IntervalDomain>::new(program.clone(), options).eval_prog().unwrap();
+            let values : Vec<&str> = report.prints().iter().map(|p| p.values[0].1.as_str()).collect();
+            assert_eq!(values, ["[-1294967296;-1294967296]", "[2147483647;2147483647]"]);
+            let alarms : Vec<_> = report.alarms().iter().map(|a| (a.error, a.position(src))).collect();
+            if integers == IntSemantics::Error {
+                assert_eq!(alarms, [
+                    (AnalysisError::Overflow, Some((1, 21))),
+                    (AnalysisError::Overflow, Some((2, 31))),
+                    (AnalysisError::Overflow, Some((3, 23))),
+                ]);
             }
             else {
-                self.base = curr_res?;
+                assert!(alarms.is_empty());
             }
         }
-        self.show_results(res);
-        Ok(())
+        // the loop is only left once the counter wraps around
+        let src = "{ int x; x = 0; while (x >= 0) { x = x + 1; } print(x); }";
+        let options = AnalysisOptions { integers : IntSemantics::Wrap, ..AnalysisOptions::default() };
+        let report = MonotonicFixpointIterator::<IntervalDomain>::new(parse_string(src).unwrap(), options).eval_prog().unwrap();
+        assert_eq!(report.prints()[0].values[0].1, "[-2147483648;-1]");
+        assert_eq!(printed::<IntervalDomain>(src), ["x : bot"]);
+        // the assertions are checked on the 32-bit values
+        let src = "{ int x; x = 2147483647; assert(x + 1 > x); }";
+        for integers in [IntSemantics::Wrap, IntSemantics::Error] {
+            let options = AnalysisOptions { integers, ..AnalysisOptions::default() };
+            let report = MonotonicFixpointIterator::<Initialization<IntervalDomain>>::new(parse_string(src).unwrap(), options).eval_prog().unwrap();
+            let alarms : Vec<_> = report.alarms().iter().map(|a| (a.error, a.position(src))).collect();
+            if integers == IntSemantics::Error {
+                assert_eq!(alarms, [(AnalysisError::Overflow, Some((1, 33))), (AnalysisError::AssertAlwaysFails, Some((1, 33)))]);
+            }
+            else {
+                assert_eq!(alarms, [(AnalysisError::AssertAlwaysFails, Some((1, 33)))]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_uninitialized() {
+        type D = Initialization<IntervalDomain>;
+        assert_eq!(alarms::<D>(include_str!("../../test/0008_noinit.c")), [
+            (AnalysisError::Uninitialized, Some((3, 3))),
+            (AnalysisError::DeadCode, Some((4, 7))),
+        ]);
+        let src = "{ int x; int y;\n  if (rand(0,1) == 0) { x = 2; }\n  y = x + 1;\n  y = 5 / x; }";
+        assert_eq!(alarms::<D>(src), [
+            (AnalysisError::MayBeUninitialized, Some((3, 7))),
+            (AnalysisError::MayBeUninitialized, Some((4, 11))),
+            (AnalysisError::DivisionByZero, Some((4, 7))),
+        ]);
+        // the read value may be any integer
+        assert_eq!(printed::<D>("{ int x; if (rand(0,1) == 0) { x = 2; } print(x); }"), ["x : [-inf;+inf]"]);
+        // only the variables which may be uninitialized are forgotten
+        let src = "{ int x; int y; y = 1; if (rand(0,1) == 0) { x = 2; } print(y); assert(y == 1); print(x); }";
+        assert_eq!(alarms::<Initialization<ConcreteDomain>>(src), [(AnalysisError::MayBeUninitialized, Some((1, 81)))]);
+        assert_eq!(printed::<Initialization<ConcreteDomain>>(src), ["y : {1}", "x : top"]);
+        // the right operand of a disjunction is only read when the left one does not hold
+        let src = "{ int x; int y; x = rand(0,1); if (x == 0 || y > 1) { print(x); } }";
+        assert_eq!(alarms::<D>(src), [(AnalysisError::Uninitialized, Some((1, 46)))]);
+        assert_eq!(printed::<D>(src), ["x : [0;0]"]);
+        // no execution goes on after the read of an uninitialized variable
+        assert_eq!(printed::<D>("{ int x; int y; y = x; print(y); }"), ["y : bot"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unreachable() {
+        // the dead branch of an if
+        assert_eq!(alarms::<IntervalDomain>(include_str!("../../test/0101_if_false.c")), [(AnalysisError::DeadCode, Some((4, 19)))]);
+        // the body of a loop which is never entered, and the statements following a halt
+        let src = "{ int x; x = 5;\n  while (x < 3) { x = x + 1; }\n  halt;\n  x = 2; print(x); }";
+        assert_eq!(alarms::<IntervalDomain>(src), [
+            (AnalysisError::DeadCode, Some((2, 17))),
+            (AnalysisError::DeadCode, Some((4, 7))),
+        ]);
+        assert_eq!(printed::<IntervalDomain>(src), ["x : bot"]);
+        let src = "{ int x; x = rand(0,1); if (x == 0) { halt; } print(x); }";
+        assert!(alarms::<IntervalDomain>(src).is_empty());
+        assert_eq!(printed::<IntervalDomain>(src), ["x : [1;1]"]);
+    }
+
+    #[test]
+    fn test_conditions() {
+        let src = "{ int x; x = rand(0,10); if (!(x < 3)) { print(x); } else { print(x); } }";
+        assert_eq!(printed::<IntervalDomain>(src), ["x : [3;10]", "x : [0;2]"]);
+        // the right operand of a conjunction is refined by the left one
+        let src = "{ int x; int y; x = rand(0,10); y = rand(0,10); if (y > 5 && x == y) { print(x); } }";
+        assert_eq!(printed::<IntervalDomain>(src), ["x : [6;10]"]);
+        let src = "{ int x; x = rand(0,10); while (!(x >= 4 || x == 0)) { print(x); x = x + 4; } }";
+        assert_eq!(printed::<IntervalDomain>(src), ["x : [1;3]"]);
+    }
+
+    #[test]
+    fn test_local_iterations() {
+        let src = "{ int x; int y; x = rand(0,10); y = rand(0,10); if (x <= y && y <= x - 1) { print(x); } }";
+        let program = parse_string(src).unwrap();
+        let values = |local_iter| {
+            let options = AnalysisOptions { local_iter, ..AnalysisOptions::default() };
+            let report = MonotonicFixpointIterator::<IntervalDomain>::new(program.clone(), options).eval_prog().unwrap();
+            report.prints()[0].values[0].1.clone()
+        };
+        assert_eq!(values(1), "[1;10]");
+        assert_eq!(values(2), "[2;9]");
+        // the condition never holds
+        assert_eq!(values(10), "bot");
+    }
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(thresholds(vec![5, 0, 5], &[1000]), [-1, 0, 1, 4, 5, 6, 1000]);
+        // the widenings stop at the constants of the program
+        assert_eq!(printed::<IntervalDomain>(include_str!("../../test/0406_loop_limit.c")), ["x : [0;100]"]);
+        assert_eq!(printed::<IntervalDomain>(include_str!("../../test/0407_loop_limit.c")), ["x : [0;100]"]);
+        // the bound 52 only appears with an extra threshold
+        let src = "{ int x; x = 0; while (rand(0,1) == 0) { if (x < 50) x = x + 3; } print(x); }";
+        assert_eq!(printed::<IntervalDomain>(src), ["x : [0;+inf]"]);
+        let options = AnalysisOptions { thresholds : vec![52], ..AnalysisOptions::default() };
+        let report = MonotonicFixpointIterator::<IntervalDomain>::new(parse_string(src).unwrap(), options).eval_prog().unwrap();
+        assert_eq!(report.prints()[0].values[0].1, "[0;52]");
+    }
+
+    #[test]
+    fn test_congruences() {
+        type D = CongruenceDomain;
+        let src = "{ int i; i = 0; while (rand(0,1) == 0) { i = i + 4; } assert(i % 2 == 0); assert(i % 8 == 0); print(i); }";
+        assert_eq!(alarms::<D>(src), [(AnalysisError::AssertMayFail, Some((1, 82)))]);
+        assert_eq!(printed::<D>(src), ["i : 8Z+0"]);
+        let src = "{ int i; i = 1; while (i < 100) { i = i + 2; } assert(i % 2 == 0); }";
+        assert_eq!(alarms::<D>(src), [(AnalysisError::AssertAlwaysFails, Some((1, 55)))]);
+    }
+}
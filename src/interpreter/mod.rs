@@ -3,4 +3,7 @@
  * This source code is licensed under the MIT license found in the
  * LICENSE file in the root directory of this source tree.
  */
-pub mod interpreter;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod interpreter;
+pub mod report;
+pub mod worklist;
@@ -0,0 +1,194 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+//! results of an analysis, which can be queried without reading its output.
+use lrpar::Span;
+use crate::ast::*;
+use crate::domains::domain::AbstractDomain;
+
+use super::interpreter::AnalysisError;
+
+/// identifier of a statement : its rank among the `statements` of the program.
+pub type NodeId = usize;
+
+/// alarm raised by the analysis on a statement, and on the expression
+/// of span `span` when it comes from one.
+#[derive(Debug, Clone)]
+pub struct Alarm {
+    pub error : AnalysisError,
+    pub node : NodeId,
+    pub span : Option<Span>,
+}
+
+impl Alarm {
+    /// line and column of the alarm inside the source `src`, both starting at 1.
+    pub fn position(&self, src : &str) -> Option<(usize, usize)> {
+        let start = self.span?.start();
+        let before = src.get(..start)?;
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        Some((line, column))
+    }
+}
+
+/// values of the variables displayed by a `print` statement.
+#[derive(Debug, Clone)]
+pub struct PrintedValues {
+    pub node : NodeId,
+    /// names of the variables and their values.
+    pub values : Vec<(String, String)>,
+}
+
+/// states computed before and after each statement, with the alarms and the
+/// displayed values. The states of a statement inside a loop are the joins
+/// of its states at every iteration once the loop invariant is found.
+#[derive(Clone)]
+pub struct AnalysisReport<D : AbstractDomain> {
+    before : Vec<Option<D>>,
+    after : Vec<Option<D>>,
+    /// whether each assertion may hold, and may fail, in the states it is checked in.
+    asserts : Vec<(bool, bool)>,
+    alarms : Vec<Alarm>,
+    prints : Vec<PrintedValues>,
+}
+
+impl<D : AbstractDomain> AnalysisReport<D> {
+    /// constructor for an empty report over `size` statements.
+    pub fn new(size : usize) -> Self {
+        AnalysisReport {
+            before : vec![None; size],
+            after : vec![None; size],
+            asserts : vec![(false, false); size],
+            alarms : Vec::new(),
+            prints : Vec::new(),
+        }
+    }
+
+    fn record(states : &mut [Option<D>], node : NodeId, state : &D) {
+        states[node] = Some(match states[node].take() {
+            Some(old) => old.join(state.clone()),
+            None => state.clone(),
+        });
+    }
+
+    pub(crate) fn record_before(&mut self, node : NodeId, state : &D) {
+        Self::record(&mut self.before, node, state)
+    }
+
+    pub(crate) fn record_after(&mut self, node : NodeId, state : &D) {
+        Self::record(&mut self.after, node, state)
+    }
+
+    /// records the states where the assertion `node` holds and where it fails.
+    pub(crate) fn record_assert(&mut self, node : NodeId, holds : &D, fails : &D) {
+        let (may_hold, may_fail) = &mut self.asserts[node];
+        *may_hold |= !holds.is_bottom();
+        *may_fail |= !fails.is_bottom();
+    }
+
+    /// adds an alarm, unless it was already raised by another evaluation of the statement.
+    pub(crate) fn push_alarm(&mut self, error : AnalysisError, node : NodeId, span : Option<Span>) {
+        if !self.alarms.iter().any(|a| a.error == error && a.node == node && a.span == span) {
+            self.alarms.push(Alarm { error, node, span })
+        }
+    }
+
+    /// raises the alarms of the assertions of `program` from their recorded outcomes :
+    /// an assertion may fail when its negation holds in some states, and always fails
+    /// when it holds in none.
+    pub(crate) fn collect_alarms(&mut self, program : &Program) {
+        for (node, stmt) in statements(program).into_iter().enumerate() {
+            if let TNode::Assert { cond } = stmt {
+                let error =
+                    match self.asserts[node] {
+                        (false, true) => AnalysisError::AssertAlwaysFails,
+                        (true, true) => AnalysisError::AssertMayFail,
+                        _ => continue,
+                    };
+                self.push_alarm(error, node, Some(*cond.get_span()));
+            }
+        }
+        self.alarms.sort_by_key(|a| a.node);
+    }
+
+    /// reports the statements of `program` which are never reached. Only the
+    /// first statement of a sequence is reported, and not the ones it contains.
+    pub(crate) fn collect_unreachable(&mut self, program : &Program) {
+        fn visit<D : AbstractDomain>(report : &mut AnalysisReport<D>, list : &[TNode], mut node : NodeId) {
+            for stmt in list {
+                if report.before[node].as_ref().is_none_or(D::is_bottom) {
+                    report.push_alarm(AnalysisError::DeadCode, node, Some(*stmt.get_span()));
+                    return;
+                }
+                match stmt {
+                    TNode::Block { stmt, .. } => visit(report, stmt, node + 1),
+                    TNode::If { then, otherwise, .. } => {
+                        visit(report, std::slice::from_ref(then), node + 1);
+                        if let Some(o) = otherwise {
+                            visit(report, std::slice::from_ref(o), node + 1 + then.size());
+                        }
+                    },
+                    TNode::While { body, .. } => visit(report, std::slice::from_ref(body), node + 1),
+                    _ => (),
+                }
+                node += stmt.size();
+            }
+        }
+        visit(self, program, 0);
+        self.alarms.sort_by_key(|a| a.node);
+    }
+
+    /// reads the values of the `print` statements of `program` in their states.
+    pub(crate) fn collect_prints(&mut self, program : &Program) {
+        for (node, stmt) in statements(program).into_iter().enumerate() {
+            if let (TNode::Print { vars, .. }, Some(state)) = (stmt, &self.before[node]) {
+                let mut state = state.clone();
+                let values = vars
+                    .iter()
+                    .map(|x| (x.get_name().to_string(), state.print(x.clone())))
+                    .collect();
+                self.prints.push(PrintedValues { node, values });
+            }
+        }
+    }
+
+    /// state before the statement `node`, `None` when it is never reached.
+    pub fn before(&self, node : NodeId) -> Option<&D> {
+        self.before[node].as_ref()
+    }
+
+    /// state after the statement `node`, `None` when its evaluation never ends.
+    pub fn after(&self, node : NodeId) -> Option<&D> {
+        self.after[node].as_ref()
+    }
+
+    pub fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+
+    pub fn prints(&self) -> &[PrintedValues] {
+        &self.prints
+    }
+
+    /// pretty printing of the displayed values, then of the alarms located in the source `src`.
+    pub fn show(&self, program : &Program, src : &str) {
+        for p in &self.prints {
+            let fmt = p.values
+                .iter()
+                .fold(String::from(""),
+                    |acc, (name, value)| -> String { format!("{}, {} : {}", acc, name, value) });
+            println!("{}", fmt);
+        }
+        let nodes = statements(program);
+        for alarm in &self.alarms {
+            match alarm.position(src) {
+                Some((line, column)) =>
+                    println!("{} at line {}, column {}, in statement :", alarm.error, line, column),
+                None => println!("{} in statement :", alarm.error),
+            }
+            display_tnode(nodes[alarm.node], 0);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+//! chaotic iterations over the control-flow graph, following a weak
+//! topological ordering of its points (Bourdoncle, 1993).
+use std::fmt;
+use lrpar::Span;
+use crate::cfg::{Cfg, Instr, Point};
+use crate::domains::domain::AbstractDomain;
+
+use super::interpreter::{cond_nonzero_divisors, nonzero_divisors, refine, thresholds, AnalysisError, AnalysisOptions};
+
+/// component of a weak topological ordering : a point, or a cycle
+/// made of a head, where widenings are applied, and of components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    Vertex(Point),
+    Cycle(Point, Vec<Component>),
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Component::Vertex(p) => write!(f, "{}", p),
+            Component::Cycle(h, body) => {
+                write!(f, "({}", h)?;
+                for c in body {
+                    write!(f, " {}", c)?;
+                }
+                write!(f, ")")
+            },
+        }
+    }
+}
+
+/// computation of the ordering from the depth-first numbering of the points.
+struct Ordering<'a> {
+    cfg : &'a Cfg,
+    dfn : Vec<usize>,
+    stack : Vec<Point>,
+    num : usize,
+}
+
+impl Ordering<'_> {
+    /// visits the point `v`, and pushes its component in `partition`
+    /// when `v` is the head of a strongly connected component.
+    fn visit(&mut self, v : Point, partition : &mut Vec<Component>) -> usize {
+        self.stack.push(v);
+        self.num += 1;
+        self.dfn[v] = self.num;
+        let mut head = self.num;
+        let mut is_loop = false;
+        let succs : Vec<Point> = self.cfg.successors(v).map(|e| e.dst).collect();
+        for w in succs {
+            let min = if self.dfn[w] == 0 { self.visit(w, partition) } else { self.dfn[w] };
+            if min <= head {
+                head = min;
+                is_loop = true;
+            }
+        }
+        if head == self.dfn[v] {
+            self.dfn[v] = usize::MAX;
+            let mut element = self.stack.pop();
+            if is_loop {
+                while let Some(w) = element.filter(|w| *w != v) {
+                    self.dfn[w] = 0;
+                    element = self.stack.pop();
+                }
+                partition.push(self.component(v));
+            }
+            else {
+                partition.push(Component::Vertex(v));
+            }
+        }
+        head
+    }
+
+    fn component(&mut self, v : Point) -> Component {
+        let mut partition = Vec::new();
+        let succs : Vec<Point> = self.cfg.successors(v).map(|e| e.dst).collect();
+        for w in succs {
+            if self.dfn[w] == 0 {
+                self.visit(w, &mut partition);
+            }
+        }
+        // the components are found in reverse order
+        partition.reverse();
+        Component::Cycle(v, partition)
+    }
+}
+
+/// weak topological ordering of the points reachable from the entry of `cfg`.
+pub fn wto(cfg : &Cfg) -> Vec<Component> {
+    let mut ordering = Ordering { cfg, dfn : vec![0; cfg.size], stack : Vec::new(), num : 0 };
+    let mut partition = Vec::new();
+    ordering.visit(cfg.entry, &mut partition);
+    partition.reverse();
+    partition
+}
+
+/// abstract states of every program point, bottom for the unreachable ones.
+#[derive(Clone)]
+pub struct InvariantMap<D : AbstractDomain> {
+    states : Vec<D>,
+    /// divisions and remainders whose divisor may be zero in these states.
+    zeros : Vec<Span>,
+}
+
+impl<D : AbstractDomain> InvariantMap<D> {
+    /// getter for the state of the program point `p`.
+    pub fn get(&self, p : Point) -> &D {
+        &self.states[p]
+    }
+
+    /// iterator over the program points and their states.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &D)> {
+        self.states.iter().enumerate()
+    }
+
+    /// spans of the divisions and remainders whose divisor may be zero.
+    pub fn divisions_by_zero(&self) -> &[Span] {
+        &self.zeros
+    }
+}
+
+/// solver computing the invariants of the points of a control-flow graph.
+pub struct WorklistSolver<'a, D : AbstractDomain> {
+    cfg : &'a Cfg,
+    options : AnalysisOptions,
+    /// sorted thresholds of the widenings.
+    thresholds : Vec<i64>,
+    states : Vec<D>,
+}
+
+impl<'a, D> WorklistSolver<'a, D>
+where D : AbstractDomain {
+    /// constructor for a new solver : the entry of the graph receives the top state.
+    pub fn new(cfg : &'a Cfg, options : AnalysisOptions) -> Self {
+        Self {
+            cfg,
+            thresholds : thresholds(cfg.constants(), &options.thresholds),
+            options,
+            states : vec![D::bottom(); cfg.size],
+        }
+    }
+
+    /// state reached by the instruction `instr` from the state `ctx`, where the divisors
+    /// are not zero : the spans of the divisions whose divisor may be zero are pushed to `zeros`.
+    fn transfer(&self, instr : &Instr, ctx : &D, zeros : &mut Vec<Span>) -> Result<D, AnalysisError> {
+        let mut ctx = ctx.clone();
+        let iterations = self.options.local_iter;
+        match instr {
+            Instr::Assign { var, expr } => nonzero_divisors(expr, ctx, zeros).assign(var, expr),
+            Instr::Guard { cond, value } => {
+                let ctx = cond_nonzero_divisors(&cond.nnf(*value), ctx, zeros);
+                Ok(refine(ctx, cond, *value, iterations))
+            },
+            Instr::Enter(var) => Ok(ctx.add_variable(var)),
+            Instr::Exit(var) => Ok(ctx.remove_variable(var)),
+            Instr::Assert(cond) => {
+                let ctx = cond_nonzero_divisors(&cond.nnf(true), ctx, zeros);
+                Ok(refine(ctx, cond, true, iterations))
+            },
+            Instr::Print(_) => Ok(ctx),
+        }
+    }
+
+    /// join of the states flowing into the point `p`.
+    fn input(&self, p : Point) -> Result<D, AnalysisError> {
+        let init = if p == self.cfg.entry { D::initial_state(&self.options) } else { D::bottom() };
+        self.cfg.predecessors(p).try_fold(init, |acc, e| {
+            Ok(acc.join(self.transfer(&e.instr, &self.states[e.src], &mut Vec::new())?))
+        })
+    }
+
+    /// iterates over the components in order, stabilizing each cycle before the next one.
+    fn iterate(&mut self, components : &[Component]) -> Result<(), AnalysisError> {
+        for c in components {
+            match c {
+                Component::Vertex(p) => self.states[*p] = self.input(*p)?,
+                Component::Cycle(h, body) => self.stabilize(*h, body)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// fixpoint of the cycle of head `h` : starting from its input, joins for the first
+    /// `delay` iterations, then widenings with thresholds until a post-fixpoint, followed
+    /// by decreasing iterations.
+    fn stabilize(&mut self, h : Point, body : &[Component]) -> Result<(), AnalysisError> {
+        // a self loop on the head must not see its state from a previous stabilization
+        self.states[h] = D::bottom();
+        self.states[h] = self.input(h)?;
+        self.iterate(body)?;
+        let mut increasing : u32 = 0;
+        loop {
+            let next = self.input(h)?;
+            if next.subset(&self.states[h]) {
+                break;
+            }
+            let state = self.states[h].clone();
+            self.states[h] =
+                if increasing < self.options.delay {
+                    state.join(next)
+                }
+                else {
+                    state.widen_thresholds(next, &self.thresholds)
+                };
+            self.iterate(body)?;
+            increasing += 1;
+        }
+        let mut decreasing : u32 = 0;
+        while decreasing < self.options.narrow {
+            let next = self.input(h)?;
+            if self.states[h].subset(&next) {
+                break;
+            }
+            let state = self.states[h].clone();
+            self.states[h] = state.narrow(next);
+            self.iterate(body)?;
+            decreasing += 1;
+        }
+        if self.options.debug {
+            eprintln!("loop head {} : {} increasing iterations ({} joins), {} decreasing iterations",
+                h, increasing, increasing.min(self.options.delay), decreasing);
+        }
+        Ok(())
+    }
+
+    /// computes the invariants of all the points of the graph, and the divisions
+    /// whose divisor may be zero in them.
+    pub fn solve(mut self) -> Result<InvariantMap<D>, AnalysisError> {
+        self.iterate(&wto(self.cfg))?;
+        let mut zeros = Vec::new();
+        for e in &self.cfg.edges {
+            let mut spans = Vec::new();
+            self.transfer(&e.instr, &self.states[e.src], &mut spans)?;
+            for span in spans {
+                if !zeros.contains(&span) {
+                    zeros.push(span);
+                }
+            }
+        }
+        Ok(InvariantMap { states : self.states, zeros })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::file_parser::parse_string;
+    use crate::domains::interval_domain::IntervalDomain;
+    use crate::interpreter::interpreter::MonotonicFixpointIterator;
+
+    fn lower(src : &str) -> Cfg {
+        Cfg::of_program(&parse_string(src).unwrap())
+    }
+
+    /// values shown by the `print`s of the program, from the invariants of their points.
+    fn printed(src : &str) -> Vec<String> {
+        printed_with(src, AnalysisOptions::default())
+    }
+
+    fn printed_with(src : &str, options : AnalysisOptions) -> Vec<String> {
+        let cfg = lower(src);
+        let inv = WorklistSolver::<IntervalDomain>::new(&cfg, options).solve().unwrap();
+        let mut res = Vec::new();
+        for e in &cfg.edges {
+            if let Instr::Print(vars) = &e.instr {
+                let mut state = inv.get(e.src).clone();
+                res.extend(vars.iter().map(|v| format!("{} : {}", v.get_name(), state.print(v.clone()))));
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn test_wto() {
+        let cfg = lower("{ int x; x = 0; while (x < 10) { x = x + 1; } print(x); }");
+        let order : Vec<String> = wto(&cfg).iter().map(|c| c.to_string()).collect();
+        assert_eq!(order.join(" "), "0 1 (2 3) 4 5 6");
+        let cfg = lower("{ int i; while (i < 5) { while (rand(0,1) == 0) { i = i + 1; } } }");
+        let order : Vec<String> = wto(&cfg).iter().map(|c| c.to_string()).collect();
+        assert_eq!(order.join(" "), "0 (1 (2 3)) 4 5");
+    }
+
+    #[test]
+    fn test_loops() {
+        assert_eq!(printed(include_str!("../../test/0411_loop_exit.c")), ["x : [10;10]"]);
+        assert_eq!(printed(include_str!("../../test/0412_loop_exit_rand.c")), ["x : [21;23]"]);
+        let src = "{ int i; int j; i = 0; j = 0; while (i < 10) { j = 0; while (j < i) { j = j + 1; } i = i + 1; } print(i, j); }";
+        assert_eq!(printed(src), ["i : [10;10]", "j : [0;9]"]);
+    }
+
+    #[test]
+    fn test_delay() {
+        // `-delay n` does the same number of joins as the recursive iterator
+        let src = "{ int x; x = 0; while (rand(0,1) == 0) { if (x < 10) { x = x + 7; } } print(x); }";
+        for delay in 0..5 {
+            let options = AnalysisOptions { delay, narrow : 0, ..AnalysisOptions::default() };
+            let expected : Vec<String> =
+                MonotonicFixpointIterator::<IntervalDomain>::new(parse_string(src).unwrap(), options.clone())
+                    .eval_prog()
+                    .unwrap()
+                    .prints()
+                    .iter()
+                    .flat_map(|p| p.values.iter().map(|(name, value)| format!("{} : {}", name, value)))
+                    .collect();
+            assert_eq!(printed_with(src, options), expected);
+        }
+        assert_eq!(printed_with(src, AnalysisOptions { delay : 2, narrow : 0, ..AnalysisOptions::default() }), ["x : [0;+inf]"]);
+        assert_eq!(printed_with(src, AnalysisOptions { delay : 3, narrow : 0, ..AnalysisOptions::default() }), ["x : [0;16]"]);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let cfg = lower("{ int x; x = 1; if (x > 2) { print(x); } halt; print(x); }");
+        let inv = WorklistSolver::<IntervalDomain>::new(&cfg, AnalysisOptions::default()).solve().unwrap();
+        let bottoms = inv.iter().filter(|(_, state)| state.is_bottom()).count();
+        // the point inside the then branch, and the three points after the halt
+        assert_eq!(bottoms, 4);
+        assert!(!inv.get(cfg.entry).is_bottom());
+    }
+
+    #[test]
+    fn test_divisions() {
+        let src = "{ int x; int y; x = rand(0,3);\n  if (x == 0 || 10 / x > 4) { y = 1; }\n  y = 20 / x;\n  y = x - 1; y = 12 % y; }";
+        let cfg = lower(src);
+        let inv = WorklistSolver::<IntervalDomain>::new(&cfg, AnalysisOptions::default()).solve().unwrap();
+        let divisions : Vec<&str> = inv.divisions_by_zero().iter().map(|s| &src[s.start()..s.end()]).collect();
+        // the division guarded by the disjunction is safe, and the
+        // analysis goes on with the nonzero divisors
+        assert_eq!(divisions, ["20 / x", "12 % y"]);
+    }
+}
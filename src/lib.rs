@@ -4,6 +4,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 pub mod ast;
+pub mod cfg;
 pub mod domains;
 pub mod error;
 pub mod frontend;
@@ -17,7 +17,8 @@ impl fmt::Display for MapError {
 
 impl Error for MapError {}
 
-trait MapTrait<K, V>
+/// interface of the persistent maps used to represent abstract environments.
+pub trait MapTrait<K, V>
 where K : Ord {
     fn new() -> Self;
     fn singleton(key : &K, value : &V) -> Self;
@@ -27,19 +28,19 @@ where K : Ord {
     fn is_empty(&self) -> bool;
 
     fn add(&mut self, key : &K, value : &V) -> Result<(), MapError>;
-    fn remove(&mut self, key : &K) -> ();
+    fn remove(&mut self, key : &K);
 
-    fn map<F : FnMut(&V) -> V>(&mut self, f : F) -> ();
-    fn iter<F : FnMut(&K, &V) -> ()>(&self, f : F) -> ();
+    fn map<F : FnMut(&V) -> V>(&mut self, f : F);
+    fn iter<F : FnMut(&K, &V)>(&self, f : F);
     fn fold<F : FnMut(&K, &V, &V) -> V>(&self, base : &V, f : F) -> V;
-    fn filter<F : FnMut(&K, &V) -> bool>(&mut self, f : F) -> ();
-    fn mapi<F : FnMut(&K, &V) -> V>(&mut self, f : F) -> ();
+    fn filter<F : FnMut(&K, &V) -> bool>(&mut self, f : F);
+    fn mapi<F : FnMut(&K, &V) -> V>(&mut self, f : F);
     fn for_all<F : FnMut(&K, &V) -> bool>(&self, f : F) -> bool;
 
     fn map2z<F : FnMut(&V, &V) -> V>
     (&mut self, other : &Self, f : F)
         -> Result<(), MapError>;
-    fn iter2z<F : FnMut(&K, &V, &V) -> ()>
+    fn iter2z<F : FnMut(&K, &V, &V)>
     (&self, other : &Self, f : F)
         -> Result<(), MapError>;
     fn fold2z<F : FnMut(&K, &V, &V, &V) -> V>
@@ -53,15 +54,22 @@ where K : Ord {
     fn max_binding(&self) -> Option<(&K, &V)>;
 }
 
+/// a subtree of the AVL : `None` stands for the empty tree.
+type Tree<D> = Option<Rc<Node<D>>>;
+
 #[derive(Clone)]
 struct Node<D> {
     key : Symbol,
     value : D,
-    left : Option<Rc<Node<D>>>,
-    right : Option<Rc<Node<D>>>,
+    left : Tree<D>,
+    right : Tree<D>,
     height : u32,
 }
 
+fn height<D>(tree : &Tree<D>) -> u32 {
+    tree.as_ref().map_or(0, |node| node.height)
+}
+
 impl<D> Node<D>
 where D : Clone + Eq {
     fn new(key : &Symbol, value : &D) -> Self {
@@ -74,531 +82,246 @@ where D : Clone + Eq {
         }
     }
 
-    fn height(&self) -> u32 {
-        self.height
-    }
-
     fn mem(&self, key : &Symbol) -> bool {
-        if self.key == *key {
-            true
-        }
-        else if self.key < *key {
-            match &self.left {
-                None => false,
-                Some(lhs) => {
-                    lhs.mem(key)
-                },
-            }
-        }
-        else {
-            match &self.right {
-                None => false,
-                Some(rhs) => {
-                    rhs.mem(key)
-                },
-            }
-        }
+        self.find(key).is_some()
     }
 
     fn find(&self, key : &Symbol) -> Option<&D> {
-        if self.key == *key {
-            Some(&self.value)
-        }
-        else if self.key < *key {
-            match &self.left {
-                None => None,
-                Some(lhs) => {
-                    lhs.find(key)
-                },
-            }
-        }
-        else {
-            match &self.right {
-                None => None,
-                Some(rhs) => {
-                    rhs.find(key)
-                },
-            }
-        }
+        let next =
+            match key.cmp(&self.key) {
+                std::cmp::Ordering::Equal => return Some(&self.value),
+                std::cmp::Ordering::Less => &self.left,
+                std::cmp::Ordering::Greater => &self.right,
+            };
+        next.as_ref().and_then(|node| node.find(key))
     }
 
-    fn create(&self, key : &Symbol, value : &D, r : Option<&Self>) -> Self {
-        let hl = self.height;
-        let hr = r.map(|x| x.height()).unwrap_or(0);
-        let height =
-            if hl >= hr { hl + 1 } else { hr + 1 };
-        Node {
+    /// builds a node from two subtrees whose heights differ by at most 2.
+    fn create(left : Tree<D>, key : &Symbol, value : &D, right : Tree<D>) -> Rc<Self> {
+        let height = 1 + std::cmp::max(height(&left), height(&right));
+        Rc::new(Node {
             key: key.clone(),
             value: value.clone(),
-            left: Some(Rc::new(self.clone())),
-            right: r.map(|node| Rc::new(node.clone())),
-            height: height,
-        }
+            left,
+            right,
+            height,
+        })
     }
 
-    fn bal(&self, key : &Symbol, value : &D, r : Option<&Self>) -> Result<Self, MapError> {
-        let hl = self.height;
-        let hr = r.map(|x| x.height()).unwrap_or(0);
-
-        if hl > hr + 2 {
-            match &self.left {
-                None => Err(MapError {}),
-                Some(lhs) => {
-                    if lhs.height() >= lhs.right.as_ref().map_or(0, |x| x.height()) {
-                        Ok(lhs.create(
-                            &lhs.key,
-                            &lhs.value,
-                            Some(&self.create(key, value, r))
-                        ))
-                    } else {
-                        match &lhs.right {
-                            None => Err(MapError {}),
-                            Some(lr) => {
-                                Ok(lhs.create(
-                                    &lhs.key,
-                                    &lhs.value,
-                                    Some(&lr.create(&lr.key,
-                                        &lr.value,
-                                        Some(&self.create(key, value, r)))
-                                    )
-                                ))
-                            }
-                        }
-                    }
+    /// same as `create`, but performs one rotation if the heights
+    /// of the subtrees are too far apart.
+    fn bal(left : Tree<D>, key : &Symbol, value : &D, right : Tree<D>) -> Rc<Self> {
+        let hl = height(&left);
+        let hr = height(&right);
+        match (&left, &right) {
+            (Some(l), _) if hl > hr + 2 => {
+                match &l.right {
+                    Some(lr) if height(&l.left) < height(&l.right) =>
+                        Self::create(
+                            Some(Self::create(l.left.clone(), &l.key, &l.value, lr.left.clone())),
+                            &lr.key,
+                            &lr.value,
+                            Some(Self::create(lr.right.clone(), key, value, right))
+                        ),
+                    _ =>
+                        Self::create(
+                            l.left.clone(),
+                            &l.key,
+                            &l.value,
+                            Some(Self::create(l.right.clone(), key, value, right))
+                        ),
                 }
-            }
-        } else if hr > hl + 2 {
-            match r {
-                None => Err(MapError {}),
-                Some(rhs) => {
-                    if rhs.height() >= rhs.left.as_ref().map_or(0, |x| x.height()) {
-                        Ok(self.create(
-                            key,
-                            value,
-                            Some(&rhs.create(
-                                &rhs.key,
-                                &rhs.value,
-                                rhs.right.as_deref()
-                            ))
-                        ))
-                    } else {
-                        match &rhs.left {
-                            None => return Err(MapError {}),
-                            Some(rl) => {
-                                Ok(self.create(
-                                    key,
-                                    value,
-                                    Some(&rl.create(
-                                        &rl.key,
-                                        &rl.value,
-                                        Some(&rhs.create(
-                                            &rhs.key,
-                                            &rhs.value,
-                                            rhs.right.as_deref()
-                                        ))
-                                    ))
-                                ))
-                            }
-                        }
-                    }
+            },
+            (_, Some(r)) if hr > hl + 2 => {
+                match &r.left {
+                    Some(rl) if height(&r.right) < height(&r.left) =>
+                        Self::create(
+                            Some(Self::create(left, key, value, rl.left.clone())),
+                            &rl.key,
+                            &rl.value,
+                            Some(Self::create(rl.right.clone(), &r.key, &r.value, r.right.clone()))
+                        ),
+                    _ =>
+                        Self::create(
+                            Some(Self::create(left, key, value, r.left.clone())),
+                            &r.key,
+                            &r.value,
+                            r.right.clone()
+                        ),
                 }
-            }
-        } else {
-            Ok(Node {
-                key: key.clone(),
-                value: value.clone(),
-                left: Some(Rc::new(self.clone())),
-                right: r.map(|node| Rc::new(node.clone())),
-                height: if hl >= hr { hl + 1 } else { hr + 1 },
-            })
-        }
-    }
-
-    fn merge(&self, other : Option<&Self>) -> Result<Self, MapError> {
-        match other {
-            Some(n) => {
-                let (s, d) = n.min_binding();
-                self.bal(s, d, n.remove_min_binding().as_ref())
             },
-            None => Ok(self.clone()),
+            _ => Self::create(left, key, value, right),
         }
     }
 
-    fn add(&self, key : &Symbol, value : &D) -> Result<Self, MapError> {
-        // is it truly correct ? to verify
-        if self.key == *key {
-            Ok(Node {
-                key : self.key.clone(),
-                value : value.clone(),
-                left : self.left.clone(),
-                right : self.right.clone(),
-                height : self.height,
-            })
-        }
-        else if *key < self.key {
-            if let Some(n) = &self.left {
-                let lhs = n.add(key, value).unwrap();
-                lhs.bal(&self.key, &self.value, self.right.as_deref())
-            }
-            else {
-                Ok(Node {
-                    key : self.key.clone(),
-                    value : value.clone(),
-                    left : Some(Self::new(key, value).into()),
-                    right : self.right.clone(),
-                    height : self.height,
-                })
-            }
-        }
-        else {
-            if let Some(n) = &self.right {
-                let rhs = n.add(key, value).unwrap();
-                self.left.clone().unwrap().bal(&self.key, &self.value, Some(&rhs))
-            }
-            else {
-                Ok(Node {
-                    key : self.key.clone(),
-                    value : value.clone(),
-                    left : self.left.clone(),
-                    right : Some(Self::new(key, value).into()),
-                    height : self.height,
-                })
-            }
-        }
-    }
-
-    fn remove(&self, key : &Symbol) -> Option<Self> {
-        if self.key == *key {
-            if let Some(n) = &self.left {
-                match n.merge(self.right.as_deref()) {
-                    Ok(node) => Some(node),
-                    Err(_) => None,
-                }
-            }
-            else if let Some(n) = &self.right {
-                match n.merge(self.left.as_deref()) {
-                    Ok(node) => Some(node),
-                    Err(_) => None,
+    fn add(tree : &Tree<D>, key : &Symbol, value : &D) -> Rc<Self> {
+        match tree {
+            None => Rc::new(Self::new(key, value)),
+            Some(node) => {
+                match key.cmp(&node.key) {
+                    std::cmp::Ordering::Equal =>
+                        Self::create(node.left.clone(), key, value, node.right.clone()),
+                    std::cmp::Ordering::Less =>
+                        Self::bal(
+                            Some(Self::add(&node.left, key, value)),
+                            &node.key,
+                            &node.value,
+                            node.right.clone()
+                        ),
+                    std::cmp::Ordering::Greater =>
+                        Self::bal(
+                            node.left.clone(),
+                            &node.key,
+                            &node.value,
+                            Some(Self::add(&node.right, key, value))
+                        ),
                 }
             }
-            else {
-                None
-            }
-        }
-        else if self.key < *key {
-            let lhs =
-                if let Some(n) = &self.left {
-                    n.remove(key)
-                }
-                else {
-                    None
-                };
-            Some(Node{
-                key : self.key.clone(),
-                value : self.value.clone(),
-                left : lhs.map(|x| x.into()),
-                right : self.right.clone(),
-                height : self.height,
-            })
-        }
-        else {
-            let rhs =
-                if let Some(n) = &self.right {
-                    n.remove(key)
-                }
-                else {
-                    None
-                };
-            Some(Node{
-                key : self.key.clone(),
-                value : self.value.clone(),
-                left : self.left.clone(),
-                right : rhs.map(|x| x.into()),
-                height : self.height,
-            })
-        }
-    }
-
-    fn iter<F : FnMut(&Symbol, &D) -> ()>(&self, mut f : F) -> () {
-        f(&self.key, &self.value);
-        if let Some(lhs) = &self.left {
-            lhs.iter(&mut f);
-        }
-        if let Some(rhs) = &self.right {
-            rhs.iter(&mut f);
         }
     }
 
-    fn fold<F : FnMut(&Symbol, &D, &D) -> D>(&self, base : &D, mut f : F) -> D {
-        match (&self.left, &self.right) {
-            (Some(lhs), Some(rhs)) => {
-                let lhs_result = &lhs.fold(base, &mut f);
-                let lhs_result= f(&self.key, &self.value, lhs_result);
-                rhs.fold(&lhs_result, &mut f)
-            }
-            (Some(lhs), None) => {
-                let lhs_result = lhs.fold(base, &mut f);
-                f(&self.key, &self.value, &lhs_result)
-            },
-            (None, Some(rhs)) =>
-                rhs.fold(&f(&self.key, &self.value, base), &mut f),
-            (None, None) =>
-                f(&self.key, &self.value, base),
+    fn min_binding(&self) -> (&Symbol, &D) {
+        match &self.left {
+            Some(lhs) => lhs.min_binding(),
+            None => (&self.key, &self.value),
         }
     }
 
-    fn filter<F : FnMut(&Symbol, &D) -> bool>(&self, f : &mut F) -> Option<Self> {
-        if f(&self.key, &self.value) {
-            return None;
+    fn max_binding(&self) -> (&Symbol, &D) {
+        match &self.right {
+            Some(rhs) => rhs.max_binding(),
+            None => (&self.key, &self.value),
         }
-        let lhs =
-            if let Some(n) = &self.left {
-                n.filter(f)
-            }
-            else {
-                None
-            };
-        let rhs =
-            if let Some(n) = &self.right {
-                n.filter(f)
-            }
-            else {
-                None
-            };
-        Some(Node {
-            key : self.key.clone(),
-            value : self.value.clone(),
-            left : lhs.map(|x| x.into()),
-            right : rhs.map(|x| x.into()),
-            height : self.height,
-        })
     }
 
-    fn map<F : FnMut(&D) -> D>(&self, mut f : F) -> Self {
-        let lhs =
-            &self.left.clone().map(
-                |x| x.map(&mut f).into()
-            );
-        let rhs =
-            &self.right.clone().map(
-                |x| x.map(&mut f).into()
-            );
-        Node {
-            value : f(&self.value),
-            key : self.key.clone(),
-            left : lhs.clone(),
-            right : rhs.clone(),
-            height : self.height,
+    fn remove_min_binding(&self) -> Tree<D> {
+        match &self.left {
+            None => self.right.clone(),
+            Some(lhs) =>
+                Some(Self::bal(lhs.remove_min_binding(), &self.key, &self.value, self.right.clone())),
         }
     }
 
-    fn mapi<F : FnMut(&Symbol, &D) -> D>(&self, mut f : F) -> Self {
-        let lhs =
-            &self.left.clone().map(
-                |x| x.mapi(&mut f).into()
-            );
-        let rhs =
-            &self.right.clone().map(
-                |x| x.mapi(&mut f).into()
-            );
-        Node {
-            value : f(&self.key, &self.value),
-            key : self.key.clone(),
-            left : lhs.clone(),
-            right : rhs.clone(),
-            height : self.height,
+    /// merges two trees such that all the keys of `t1` are smaller than those of `t2`.
+    fn merge(t1 : &Tree<D>, t2 : &Tree<D>) -> Tree<D> {
+        match (t1, t2) {
+            (None, t) | (t, None) => t.clone(),
+            (_, Some(n2)) => {
+                let (key, value) = n2.min_binding();
+                Some(Self::bal(t1.clone(), key, value, n2.remove_min_binding()))
+            }
         }
     }
 
-    fn for_all<F : FnMut(&Symbol, &D) -> bool>
-    (&self, mut f : F) -> bool {
-        f(&self.key, &self.value)
-        && if let Some(n) = &self.left {
-            n.for_all(&mut f)
-        } else {
-            true
-        }
-        && if let Some(n) = &self.right {
-            n.for_all(&mut f)
-        } else {
-            true
+    fn remove(tree : &Tree<D>, key : &Symbol) -> Tree<D> {
+        let node = tree.as_ref()?;
+        match key.cmp(&node.key) {
+            std::cmp::Ordering::Equal =>
+                Self::merge(&node.left, &node.right),
+            std::cmp::Ordering::Less =>
+                Some(Self::bal(Self::remove(&node.left, key), &node.key, &node.value, node.right.clone())),
+            std::cmp::Ordering::Greater =>
+                Some(Self::bal(node.left.clone(), &node.key, &node.value, Self::remove(&node.right, key))),
         }
     }
 
-    fn min_binding(&self) -> (&Symbol, &D) {
-        if let Some(n) = &self.left {
-            n.min_binding()
-        }
-        else {
-            (&self.key, &self.value)
+    /// in-order traversal of the tree.
+    fn iter<F : FnMut(&Symbol, &D)>(&self, f : &mut F) {
+        if let Some(lhs) = &self.left {
+            lhs.iter(f);
         }
-    }
-
-    fn remove_min_binding(&self) -> Option<Self> {
-        match (&self.left, &self.right) {
-            (None, None) => None,
-            (None, Some(n)) => Some((**n).clone()),
-            (Some(n), _) => {
-                let lhs = n.remove_min_binding()?;
-                Some(lhs.bal(&self.key, &self.value, self.right.as_deref()).unwrap())
-            },
+        f(&self.key, &self.value);
+        if let Some(rhs) = &self.right {
+            rhs.iter(f);
         }
     }
 
-    fn max_binding(&self) -> (&Symbol, &D) {
-        if let Some(n) = &self.right {
-            n.max_binding()
-        }
-        else {
-            (&self.key, &self.value)
-        }
-    }
-    
-    fn cut(&self, key : &Symbol)
-     -> Result<(Option<&Self>, Option<&D>, Option<&Self>), MapError> {
-        if self.key == *key {
-            Ok(
-                (self.left.as_deref(),
-                Some(&self.value),
-                self.right.as_deref())
-            )
-        }
-        else if self.key > *key {
+    fn fold<F : FnMut(&Symbol, &D, &D) -> D>(&self, base : D, f : &mut F) -> D {
+        let acc =
             match &self.left {
-                None => Ok((None, None, self.right.as_deref())),
-                Some(n) => n.cut(key),
-            }
-        }
-        else if self.key < *key {
-            match &self.right {
-                None => Ok((self.left.as_deref(), None, None)),
-                Some(n) => n.cut(key),
-            }
-        }
-        else {
-            Err(MapError {})
+                Some(lhs) => lhs.fold(base, f),
+                None => base,
+            };
+        let acc = f(&self.key, &self.value, &acc);
+        match &self.right {
+            Some(rhs) => rhs.fold(acc, f),
+            None => acc,
         }
     }
 
-    fn map2z<F : FnMut(&D, &D) -> D>
-    (&self, other : Option<&Self>, mut f : F) -> Result<Self, MapError> {
-        if let Some(other) = other {
-            let (lhs, val, rhs) =
-                other.cut(&self.key.clone())?;
-            if let Some(val) = val {
-                Ok(Node {
-                    key: self.key.clone(),
-                    value: f(&self.value, val),
-                    left: self.left
-                        .as_ref()
-                        .map(|x| x.map2z(lhs, &mut f).unwrap().into()),
-                    right: self.right
-                        .as_ref()
-                        .map(|x| x.map2z(rhs, &mut f).unwrap().into()),
-                    height: self.height,
-                })
-            }
-            else {
-                Err(MapError {})
-            }
-        }
-        else {
-            Err(MapError {})
+    fn mapi<F : FnMut(&Symbol, &D) -> D>(&self, f : &mut F) -> Self {
+        let left = self.left.as_ref().map(|x| Rc::new(x.mapi(f)));
+        let value = f(&self.key, &self.value);
+        let right = self.right.as_ref().map(|x| Rc::new(x.mapi(f)));
+        Node {
+            key : self.key.clone(),
+            value,
+            left,
+            right,
+            height : self.height,
         }
     }
 
-    fn iter2z<F : FnMut(&Symbol, &D, &D) -> ()>
-    (&self, other : Option<&Self>, mut f : F) -> Result<(), MapError> {
-        if let Some(other) = other {
-            let (lhs, val, rhs) =
-                other.cut(&self.key.clone())?;
-            if let Some(val) = val {
-                self.left
-                    .as_ref()
-                    .map(|x| x.iter2z(lhs, &mut f));
-                if self.value != *val {
-                    f(&self.key, &self.value, val);
-                }
-                self.right
-                    .as_ref()
-                    .map(|x| x.iter2z(rhs, &mut f));
-                Ok(())
-            }
-            else {
-                Err(MapError {})
-            }
-        }
-        else {
-            Err(MapError {})
-        }
+    fn for_all<F : FnMut(&Symbol, &D) -> bool>(&self, f : &mut F) -> bool {
+        self.left.as_ref().is_none_or(|x| x.for_all(f))
+        && f(&self.key, &self.value)
+        && self.right.as_ref().is_none_or(|x| x.for_all(f))
     }
 
-    fn fold2z<F : FnMut(&Symbol, &D, &D, &D) -> D>
-    (&self, other : Option<&Self>, base : &D, f : &mut F) -> Result<&D, MapError> {
-        if let Some(other) = other {
-            let (lhs, val, rhs) =
-                other.cut(&self.key.clone())?;
-            if let Some(val) = val {
-                let acc =
-                    self.left
-                    .as_ref()
-                    .map(|x| x.fold2z(lhs, base, f))
-                    .unwrap();
-                let acc = if self.value == *val {
-                    acc?
-                }
-                else {
-                    &f(&self.key, &self.value, val, acc?)
-                };
-                self.right
-                    .as_ref()
-                    .map(|x|  x.fold2z(rhs, acc, f))
-                    .unwrap_or(Err(MapError {}))
-            }
-            else {
-                Err(MapError {})
-            }
-        }
-        else {
-            Err(MapError {})
-        }
+    /// list of the bindings of the tree, sorted by key.
+    fn bindings(&self) -> Vec<(&Symbol, &D)> {
+        let mut res = Vec::new();
+        self.collect(&mut res);
+        res
     }
 
-    fn for_all2z<F : FnMut(&Symbol, &D, &D) -> bool>
-    (&self, other : Option<&Self>, f : &mut F) -> Result<bool, MapError> {
-        if let Some(other) = other {
-            let (lhs, val, rhs) =
-                other.cut(&self.key.clone())?;
-            if let Some(val) = val {
-                Ok(
-                    self.left
-                        .as_ref()
-                        .map(|x| x.for_all2z(lhs, f).unwrap())
-                        .unwrap_or(true)
-                    && (self.value == *val || f(&self.key, &self.value, val))
-                    && self.left
-                    .as_ref()
-                    .map(|x| x.for_all2z(rhs, f).unwrap())
-                    .unwrap_or(true)
-                )
-            }
-            else {
-                Err(MapError {})
-            }
+    fn collect<'a>(&'a self, acc : &mut Vec<(&'a Symbol, &'a D)>) {
+        if let Some(lhs) = &self.left {
+            lhs.collect(acc);
         }
-        else {
-            Err(MapError {})
+        acc.push((&self.key, &self.value));
+        if let Some(rhs) = &self.right {
+            rhs.collect(acc);
         }
     }
 }
 
+/// persistent map from symbols to values, implemented as an AVL tree.
+/// The functions suffixed by `2z` work on two maps with the same keys,
+/// and skip bindings that are equal in both maps whenever it is possible.
+#[derive(Clone)]
 pub struct Map<D> {
-    root : Option<Rc<Node<D>>>,
+    root : Tree<D>,
 }
 
-impl<D> Map<D> {
-    fn get_root(&self) -> Option<Rc<Node<D>>> {
-        self.root.clone()
+impl<D> PartialEq for Map<D>
+where D : Clone + Eq {
+    // two maps are equal when they hold the same bindings, whatever their shape
+    fn eq(&self, other : &Self) -> bool {
+        self.zip(other).is_ok_and(|bindings| {
+            bindings.iter().all(|(_, v1, v2)| v1 == v2)
+        })
+    }
+}
+
+impl<D> Eq for Map<D>
+where D : Clone + Eq {}
+
+impl<D> Map<D>
+where D : Clone + Eq {
+    /// zips the bindings of two maps, failing if their keys differ.
+    fn zip<'a>(&'a self, other : &'a Self) -> Result<Vec<(&'a Symbol, &'a D, &'a D)>, MapError> {
+        let lhs = self.root.as_ref().map_or(Vec::new(), |n| n.bindings());
+        let rhs = other.root.as_ref().map_or(Vec::new(), |n| n.bindings());
+        if lhs.len() != rhs.len() {
+            return Err(MapError {});
+        }
+        lhs.into_iter()
+            .zip(rhs)
+            .map(|((k1, v1), (k2, v2))| {
+                if k1 == k2 { Ok((k1, v1, v2)) } else { Err(MapError {}) }
+            })
+            .collect()
     }
 }
 
@@ -631,159 +354,100 @@ where D : Clone + Eq {
     }
 
     fn add(&mut self, key : &Symbol, value : &D) -> Result<(), MapError> {
-        match &self.root {
-            None => {
-                self.root = Some(Rc::new(Node::<D>::new(key, value)));
-                Ok(())
-            },
-            Some(node) => {
-                self.root = Some(node.add(key, value)?.into());
-                Ok(())
-            },
-        }
+        self.root = Some(Node::add(&self.root, key, value));
+        Ok(())
     }
 
-    fn remove(&mut self, key : &Symbol) -> () {
-        match &self.root {
-            None => (),
-            Some(node) => {
-                if let Some(res) = node.remove(key) {
-                    self.root = Some(res.into());
-                }
-                else {
-                    self.root = None;
-                }
-            },
-        }
+    fn remove(&mut self, key : &Symbol) {
+        self.root = Node::remove(&self.root, key);
     }
 
-    fn iter<F : FnMut(&Symbol, &D) -> ()>(&self, f : F) -> () {
-        match &self.root {
-            None => (),
-            Some(node) => node.iter(f),
+    fn iter<F : FnMut(&Symbol, &D)>(&self, mut f : F) {
+        if let Some(node) = &self.root {
+            node.iter(&mut f)
         }
     }
 
-    fn fold<F : FnMut(&Symbol, &D, &D) -> D>(&self, base : &D, f : F) -> D {
+    fn fold<F : FnMut(&Symbol, &D, &D) -> D>(&self, base : &D, mut f : F) -> D {
         match &self.root {
             None => base.clone(),
-            Some(node) => node.fold(base, f),
+            Some(node) => node.fold(base.clone(), &mut f),
         }
     }
 
-    fn filter<F : FnMut(&Symbol, &D) -> bool>(&mut self, mut f : F) -> () {
-        match &self.root {
-            None => (),
-            Some(node) => {
-                if let Some(res) = node.filter(&mut f) {
-                    self.root = Some(res.into());
-                }
-                else {
-                    self.root = None;
-                }
+    fn filter<F : FnMut(&Symbol, &D) -> bool>(&mut self, mut f : F) {
+        let mut res = Self::new();
+        self.iter(|k, v| {
+            if f(k, v) {
+                res.root = Some(Node::add(&res.root, k, v));
             }
-        }
+        });
+        self.root = res.root;
     }
 
-    fn map<F : FnMut(&D) -> D>(&mut self, f : F) -> () {
-        match &self.root {
-            None => (),
-            Some(node) => {
-                self.root = Some(node.map(f).into());
-            }
-        }
+    fn map<F : FnMut(&D) -> D>(&mut self, mut f : F) {
+        self.mapi(|_, v| f(v))
     }
 
-    fn mapi<F : FnMut(&Symbol, &D) -> D>(&mut self, f : F) -> () {
-        match &self.root {
-            None => (),
-            Some(node) => {
-                self.root = Some(node.mapi(f).into());
-            }
-        }
+    fn mapi<F : FnMut(&Symbol, &D) -> D>(&mut self, mut f : F) {
+        self.root = self.root.as_ref().map(|node| Rc::new(node.mapi(&mut f)));
     }
-    
+
     fn map2z<F : FnMut(&D, &D) -> D>
     (&mut self, other : &Self, mut f : F) -> Result<(), MapError> {
-        match (&self.root, &other.root) {
-            (None, None) => Ok(()),
-            (None, Some(_)) =>
-                Err(MapError {}),
-            (Some(_), None) =>
-                Err(MapError {}),
-            (Some(n1), Some(n2)) => {
-                self.root = Some(n1.map2z(Some(n2), &mut f)?.into());
-                Ok(())
-            },
-        }
+        self.zip(other)?;
+        self.mapi(|k, v| {
+            let w = other.find(k).expect("keys checked by zip");
+            if v == w { v.clone() } else { f(v, w) }
+        });
+        Ok(())
     }
-    
-    fn iter2z<F : FnMut(&Symbol, &D, &D) -> ()>
+
+    fn iter2z<F : FnMut(&Symbol, &D, &D)>
     (&self, other : &Self, mut f : F) -> Result<(), MapError> {
-        match (&self.root, &other.root) {
-            (None, None) => Ok(()),
-            (None, Some(_)) =>
-                Err(MapError {}),
-            (Some(_), None) =>
-                Err(MapError {}),
-            (Some(n1), Some(n2)) => {
-                n1.iter2z(Some(n2), &mut f)?;
-                Ok(())
-            },
+        for (k, v1, v2) in self.zip(other)? {
+            if v1 != v2 {
+                f(k, v1, v2);
+            }
         }
+        Ok(())
     }
-    
+
     fn fold2z<F : FnMut(&Symbol, &D, &D, &D) -> D>
     (&mut self, other : &Self, base : &D, mut f : F) -> Result<D, MapError> {
-        match (&self.root, &other.root) {
-            (None, None) => Ok(base.clone()),
-            (None, Some(_)) =>
-                Err(MapError {}),
-            (Some(_), None) =>
-                Err(MapError {}),
-            (Some(n1), Some(n2)) =>
-                Ok(n1.fold2z(Some(n2), base, &mut f)?.clone()),
+        let mut acc = base.clone();
+        for (k, v1, v2) in self.zip(other)? {
+            if v1 != v2 {
+                acc = f(k, v1, v2, &acc);
+            }
         }
+        Ok(acc)
     }
-    
+
     fn min_binding(&self) -> Option<(&Symbol, &D)> {
-        match &self.root {
-            None => None,
-            Some(node) => Some(node.min_binding()),
-        }
+        self.root.as_ref().map(|node| node.min_binding())
     }
-    
+
     fn max_binding(&self) -> Option<(&Symbol, &D)> {
-        match &self.root {
-            None => None,
-            Some(node) => Some(node.max_binding()),
-        }
+        self.root.as_ref().map(|node| node.max_binding())
     }
-    
+
     fn is_empty(&self) -> bool {
-        match &self.root {
-            None => true,
-            _ => false,
-        }
+        self.root.is_none()
     }
-    
+
     fn for_all<F : FnMut(&Symbol, &D) -> bool>(&self, mut f : F) -> bool {
         match &self.root {
             None => true,
             Some(n) => n.for_all(&mut f),
         }
     }
-    
+
     fn for_all2z<F : FnMut(&Symbol, &D, &D) -> bool>
     (&mut self, other : &Self, mut f : F) -> Result<bool, MapError> {
-        match (&self.root, &other.root) {
-            (None, None) => Ok(true),
-            (None, Some(_)) => Err(MapError {}),
-            (Some(_), None) => Err(MapError {}),
-            (Some(n1), Some(n2)) => {
-                Ok(n1.for_all2z(Some(n2), &mut f)?)
-            },
-        }
+        Ok(self.zip(other)?
+            .into_iter()
+            .all(|(k, v1, v2)| v1 == v2 || f(k, v1, v2)))
     }
 }
 
@@ -794,7 +458,7 @@ mod tests {
 
     impl Symbol {
         pub fn new(name : &str) -> Self {
-            Self::Variable { name: name.to_string(), dtype: Type::Int }
+            Self::Variable { name: name.to_string(), dtype: Type::Int, depth: 0 }
         }
     }
 
@@ -1023,4 +687,84 @@ mod tests {
         assert!(map1.for_all2z(&map2, |_, v1, v2| v1.0 > v2.0).unwrap());
         assert!(!map1.for_all2z(&map2, |_, v1, v2| v1.0 < v2.0).unwrap());
     }
+
+    /// the keys `x00`, `x01`, ... in increasing order.
+    fn keys(n : i32) -> Vec<Symbol> {
+        (0..n).map(|i| Symbol::new(&format!("x{:02}", i))).collect()
+    }
+
+    #[test]
+    fn test_many_keys() {
+        // keys inserted in increasing order used to be looked up in the wrong
+        // subtree, and inserting under a missing child overwrote the parent
+        let keys = keys(64);
+        let mut increasing = Map::new();
+        let mut decreasing = Map::new();
+        for (i, k) in keys.iter().enumerate() {
+            increasing.add(k, &TestDomain(i as i32)).unwrap();
+        }
+        for (i, k) in keys.iter().enumerate().rev() {
+            decreasing.add(k, &TestDomain(i as i32)).unwrap();
+        }
+        for (i, k) in keys.iter().enumerate() {
+            assert_eq!(increasing.find(k), Some(&TestDomain(i as i32)));
+            assert_eq!(decreasing.find(k), Some(&TestDomain(i as i32)));
+        }
+        let mut visited = Vec::new();
+        increasing.iter(|k, _| visited.push(k.clone()));
+        assert_eq!(visited, keys);
+        // the tree stays balanced
+        assert!(height(&increasing.root) <= 8);
+        increasing.add(&keys[10], &TestDomain(-1)).unwrap();
+        assert_eq!(increasing.find(&keys[10]), Some(&TestDomain(-1)));
+        assert_eq!(increasing.find(&keys[11]), Some(&TestDomain(11)));
+    }
+
+    #[test]
+    fn test_remove_many() {
+        let keys = keys(32);
+        let mut map = Map::new();
+        for (i, k) in keys.iter().enumerate() {
+            map.add(k, &TestDomain(i as i32)).unwrap();
+        }
+        for k in keys.iter().step_by(2) {
+            map.remove(k);
+        }
+        for (i, k) in keys.iter().enumerate() {
+            assert_eq!(map.find(k).cloned(), if i % 2 == 0 { None } else { Some(TestDomain(i as i32)) });
+        }
+    }
+
+    #[test]
+    fn test_2z_shapes() {
+        // the functions on two maps do not depend on the shape of the trees,
+        // and skip the bindings equal in both maps
+        let keys = keys(16);
+        let mut map1 = Map::new();
+        let mut map2 = Map::new();
+        for (i, k) in keys.iter().enumerate() {
+            map1.add(k, &TestDomain(i as i32)).unwrap();
+        }
+        for (i, k) in keys.iter().enumerate().rev() {
+            map2.add(k, &TestDomain(if i == 15 { 0 } else { i as i32 })).unwrap();
+        }
+        let mut visited = Vec::new();
+        map1.iter2z(&map2, |k, _, _| visited.push(k.clone())).unwrap();
+        assert_eq!(visited, vec![keys[15].clone()]);
+        assert_eq!(map1.fold2z(&map2, &TestDomain(0), |_, v1, v2, acc| TestDomain(acc.0 + v1.0 - v2.0)).unwrap(), TestDomain(15));
+        // the last binding lies in the right subtree
+        assert!(!map1.for_all2z(&map2, |_, v1, v2| v1.0 < v2.0).unwrap());
+        map1.map2z(&map2, |_, v2| v2.clone()).unwrap();
+        assert_eq!(map1.find(&keys[15]), Some(&TestDomain(0)));
+        assert!(map1.for_all2z(&map2, |_, _, _| false).unwrap());
+    }
+
+    #[test]
+    fn test_fold2z_singleton() {
+        let key = Symbol::new("key");
+        let mut map1 = Map::singleton(&key, &TestDomain(1));
+        let map2 = Map::singleton(&key, &TestDomain(2));
+        assert_eq!(map1.fold2z(&map2, &TestDomain(0), |_, v1, v2, acc| TestDomain(acc.0 + v1.0 + v2.0)).unwrap(), TestDomain(3));
+        assert!(map1.fold2z(&Map::new(), &TestDomain(0), |_, _, _, acc| acc.clone()).is_err());
+    }
 }
@@ -4,4 +4,5 @@
  * LICENSE file in the root directory of this source tree.
  */
 #[allow(dead_code)]
-pub mod map;
\ No newline at end of file
+pub mod map;
+pub mod rational;
@@ -0,0 +1,181 @@
+/*
+ * author : Narcisse.
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+use std::cmp::Ordering;
+use std::fmt;
+
+/// exact rational number num / den, kept in lowest terms with den > 0.
+/// Arithmetic operations are checked, and return `None` on overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num : i128,
+    den : i128,
+}
+
+/// greatest common divisor, always non-negative.
+pub fn gcd(mut a : i128, mut b : i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    // no reduction in the degenerate case gcd = 2^127
+    a.checked_abs().unwrap_or(1)
+}
+
+impl Rational {
+    pub const ZERO : Rational = Rational { num : 0, den : 1 };
+    pub const ONE : Rational = Rational { num : 1, den : 1 };
+
+    /// builds num / den, `None` when den is zero or on overflow.
+    pub fn new(num : i128, den : i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let (num, den) = (num / g, den / g);
+        if den < 0 {
+            Some(Rational { num : num.checked_neg()?, den : den.checked_neg()? })
+        }
+        else {
+            Some(Rational { num, den })
+        }
+    }
+
+    pub fn integer(n : i128) -> Self {
+        Rational { num : n, den : 1 }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.num.signum()
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> i128 {
+        -(-self.num).div_euclid(self.den)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Rational { num : self.num.checked_neg()?, den : self.den })
+    }
+
+    pub fn checked_add(self, rhs : Self) -> Option<Self> {
+        let g = gcd(self.den, rhs.den);
+        let (d1, d2) = (self.den / g, rhs.den / g);
+        let num = self.num.checked_mul(d2)?.checked_add(rhs.num.checked_mul(d1)?)?;
+        Rational::new(num, self.den.checked_mul(d2)?)
+    }
+
+    pub fn checked_sub(self, rhs : Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    // cross reductions keep the intermediate products small
+    pub fn checked_mul(self, rhs : Self) -> Option<Self> {
+        let g1 = gcd(self.num, rhs.den).max(1);
+        let g2 = gcd(rhs.num, self.den).max(1);
+        Rational::new(
+            (self.num / g1).checked_mul(rhs.num / g2)?,
+            (self.den / g2).checked_mul(rhs.den / g1)?
+        )
+    }
+
+    pub fn checked_div(self, rhs : Self) -> Option<Self> {
+        self.checked_mul(Rational::new(rhs.den, rhs.num)?)
+    }
+}
+
+impl Ord for Rational {
+    // compares the integer parts, then the inverses of the fractional parts,
+    // which never overflows
+    fn cmp(&self, rhs : &Self) -> Ordering {
+        let (a, b) = (self.floor(), rhs.floor());
+        if a != b {
+            return a.cmp(&b);
+        }
+        let (r1, r2) = (self.num.rem_euclid(self.den), rhs.num.rem_euclid(rhs.den));
+        match (r1, r2) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            _ => Rational { num : rhs.den, den : r2 }.cmp(&Rational { num : self.den, den : r1 }),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, rhs : &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        }
+        else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(num : i128, den : i128) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(q(4, -6), q(-2, 3));
+        assert_eq!(q(0, 5), Rational::ZERO);
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(q(6, 3).to_string(), "2");
+        assert_eq!(q(-3, 6).to_string(), "-1/2");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(q(1, 2).checked_add(q(1, 3)), Some(q(5, 6)));
+        assert_eq!(q(1, 2).checked_sub(q(3, 4)), Some(q(-1, 4)));
+        assert_eq!(q(2, 3).checked_mul(q(9, 4)), Some(q(3, 2)));
+        assert_eq!(q(2, 3).checked_div(q(-4, 3)), Some(q(-1, 2)));
+        assert_eq!(q(1, 2).checked_div(Rational::ZERO), None);
+        assert_eq!(Rational::integer(i128::MAX).checked_add(Rational::ONE), None);
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(q(7, 2).floor(), 3);
+        assert_eq!(q(7, 2).ceil(), 4);
+        assert_eq!(q(-7, 2).floor(), -4);
+        assert_eq!(q(-7, 2).ceil(), -3);
+        assert_eq!(q(4, 2).ceil(), 2);
+    }
+
+    #[test]
+    fn test_order() {
+        assert!(q(1, 3) < q(1, 2));
+        assert!(q(-1, 2) < q(-1, 3));
+        assert!(q(i128::MAX, i128::MAX - 1) < q(i128::MAX - 1, i128::MAX - 2));
+        assert_eq!(q(2, 4).cmp(&q(1, 2)), Ordering::Equal);
+    }
+}
@@ -13,15 +13,54 @@
 //! cargo run [ARGS]* file.c
 //! ```
 //! 1 and only 1 C file should be precised, and arguments are chosen among :
-//! `-concrete`, `-constant`, `-interval`, `-disjonctive`, `-unroll n`, `-delay n` (n : u32)
+//! `-concrete`, `-constant`, `-congruence`, `-interval`, `-octagon`, `-polyhedra`, `-disjonctive`, `-unroll n`, `-delay n`, `-narrow n` (n : u32), `-debug`,
+//! `-disjuncts n` (bound on the number of disjuncts of `-disjonctive`, 8 by default),
+//! `-machine wrap|error` (32-bit integers, wrapping around or stopping on overflow),
+//! `-local-iter n` (passes refining a state by a condition),
+//! `-thresholds n,m,...` (widening thresholds, added to the constants of the program)
 use std::env;
+use std::fmt;
 use rsabsint::frontend::file_parser::*;
 use rsabsint::ast::{display_program, Program};
+use rsabsint::interpreter::interpreter::{analyze, AnalysisOptions, IntSemantics};
+use rsabsint::domains::concrete_domain::ConcreteDomain;
+use rsabsint::domains::constant_domain::ConstantDomain;
+use rsabsint::domains::congruence_domain::CongruenceDomain;
+use rsabsint::domains::interval_domain::IntervalDomain;
+use rsabsint::domains::octagon_domain::OctagonDomain;
+use rsabsint::domains::polyhedra_domain::PolyhedraDomain;
+use rsabsint::domains::disjonctive::Disjunctive;
+
+/// abstract domains that can be selected on the command line.
+#[derive(Debug, Clone, Copy)]
+enum DomainChoice {
+    Concrete,
+    Constant,
+    Congruence,
+    Interval,
+    Octagon,
+    Polyhedra,
+}
+
+impl fmt::Display for DomainChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Concrete => write!(f, "-concrete"),
+            Self::Constant => write!(f, "-constant"),
+            Self::Congruence => write!(f, "-congruence"),
+            Self::Interval => write!(f, "-interval"),
+            Self::Octagon => write!(f, "-octagon"),
+            Self::Polyhedra => write!(f, "-polyhedra"),
+        }
+    }
+}
 
 fn help(binary_path : String) {
     println!("usage: {} [ARGS]* [file].c\n
-    [ARGS] can be '-concrete', '-constant', '-interval',
-    '-disjonctive', '-unroll n', '-delay n' (n : u32)",
+    [ARGS] can be '-concrete', '-constant', '-congruence', '-interval',
+    '-octagon', '-polyhedra', '-disjonctive', '-disjuncts n', '-unroll n',
+    '-delay n', '-narrow n', '-local-iter n' (n : u32), '-thresholds n,m,...' (n, m : i64),
+    '-debug', '-machine wrap|error'",
     binary_path);
 }
 
@@ -46,18 +85,34 @@ fn main() {
 
         let mut i : usize = 0;
         let parameters_length : usize = parameters.len();
+        let mut domain : Option<DomainChoice> = None;
+        let mut disjonctive = false;
+        let mut options = AnalysisOptions::default();
 
         while i < parameters_length {
             let str_parameter = parameters[i].trim();
             match str_parameter {
-                "-concrete" => (),
-                "-constant" => (),
-                "-interval" => (),
-                "-disjonctive" => (),
+                "-concrete" => domain = Some(DomainChoice::Concrete),
+                "-constant" => domain = Some(DomainChoice::Constant),
+                "-congruence" => domain = Some(DomainChoice::Congruence),
+                "-interval" => domain = Some(DomainChoice::Interval),
+                "-octagon" => domain = Some(DomainChoice::Octagon),
+                "-polyhedra" => domain = Some(DomainChoice::Polyhedra),
+                "-disjonctive" => disjonctive = true,
+                "-disjuncts" =>
+                    if i + 1 < parameters_length {
+                        i += 1;
+                        options.disjuncts =
+                            parameters[i].trim().parse().unwrap();
+                    }
+                    else {
+                        help(binary_path);
+                        panic!("-disjuncts without argument");
+                    },
                 "-unroll" =>
                     if i + 1 < parameters_length {
                         i += 1;
-                        let _unroll_number : u32 =
+                        options.unroll =
                             parameters[i].trim().parse().unwrap();
                     }
                     else {
@@ -67,13 +122,61 @@ fn main() {
                 "-delay" =>
                     if i + 1 < parameters_length {
                         i += 1;
-                        let _delay_number : u32 =
+                        options.delay =
                             parameters[i].trim().parse().unwrap();
                     }
                     else {
                         help(binary_path);
                         panic!("-delay without argument");
                     },
+                "-narrow" =>
+                    if i + 1 < parameters_length {
+                        i += 1;
+                        options.narrow =
+                            parameters[i].trim().parse().unwrap();
+                    }
+                    else {
+                        help(binary_path);
+                        panic!("-narrow without argument");
+                    },
+                "-local-iter" =>
+                    if i + 1 < parameters_length {
+                        i += 1;
+                        options.local_iter =
+                            parameters[i].trim().parse().unwrap();
+                    }
+                    else {
+                        help(binary_path);
+                        panic!("-local-iter without argument");
+                    },
+                "-thresholds" =>
+                    if i + 1 < parameters_length {
+                        i += 1;
+                        options.thresholds =
+                            parameters[i].split(',').map(|t| t.trim().parse().unwrap()).collect();
+                    }
+                    else {
+                        help(binary_path);
+                        panic!("-thresholds without argument");
+                    },
+                "-debug" => options.debug = true,
+                "-machine" =>
+                    if i + 1 < parameters_length {
+                        i += 1;
+                        options.integers =
+                            match parameters[i].trim() {
+                                "wrap" => IntSemantics::Wrap,
+                                "error" => IntSemantics::Error,
+                                semantics => {
+                                    help(binary_path);
+                                    panic!("unknown integer semantics {}", semantics);
+                                },
+                            };
+                    }
+                    else {
+                        help(binary_path);
+                        panic!("-machine without argument");
+                    },
                 _ => {
                     help(binary_path);
                     panic!("unknown option {}", str_parameter);
@@ -83,7 +186,53 @@ fn main() {
         }
         let program: Program =
             parse_file(target_file.to_string()).unwrap();
-        display_program(program);
+        let source = std::fs::read_to_string(target_file).unwrap();
+        // -disjonctive alone refines the interval domain
+        if disjonctive && domain.is_none() {
+            domain = Some(DomainChoice::Interval);
+        }
+        match domain {
+            None => display_program(program),
+            Some(domain) => {
+                println!("domain {}{}, unroll {}, delay {}, narrow {}, local iterations {}{}",
+                    domain, if disjonctive { format!(" -disjonctive ({} disjuncts)", options.disjuncts) } else { String::new() },
+                    options.unroll, options.delay, options.narrow, options.local_iter,
+                    match options.integers {
+                        IntSemantics::Unbounded => "",
+                        IntSemantics::Wrap => ", machine wrap",
+                        IntSemantics::Error => ", machine error",
+                    });
+                let result =
+                    match (domain, disjonctive) {
+                        // the concrete domain is already a set of environments
+                        (DomainChoice::Concrete, _) =>
+                            analyze::<ConcreteDomain>(program, &source, options),
+                        (DomainChoice::Constant, false) =>
+                            analyze::<ConstantDomain>(program, &source, options),
+                        (DomainChoice::Constant, true) =>
+                            analyze::<Disjunctive<ConstantDomain>>(program, &source, options),
+                        (DomainChoice::Congruence, false) =>
+                            analyze::<CongruenceDomain>(program, &source, options),
+                        (DomainChoice::Congruence, true) =>
+                            analyze::<Disjunctive<CongruenceDomain>>(program, &source, options),
+                        (DomainChoice::Interval, false) =>
+                            analyze::<IntervalDomain>(program, &source, options),
+                        (DomainChoice::Interval, true) =>
+                            analyze::<Disjunctive<IntervalDomain>>(program, &source, options),
+                        (DomainChoice::Octagon, false) =>
+                            analyze::<OctagonDomain>(program, &source, options),
+                        (DomainChoice::Octagon, true) =>
+                            analyze::<Disjunctive<OctagonDomain>>(program, &source, options),
+                        (DomainChoice::Polyhedra, false) =>
+                            analyze::<PolyhedraDomain>(program, &source, options),
+                        (DomainChoice::Polyhedra, true) =>
+                            analyze::<Disjunctive<PolyhedraDomain>>(program, &source, options),
+                    };
+                if let Err(e) = result {
+                    eprintln!("analysis failed : {}", e);
+                }
+            },
+        }
     }
     else {
         help(binary_path);
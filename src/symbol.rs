@@ -9,16 +9,38 @@ use lrpar::{NonStreamingLexer, Span};
 use crate::typing::*;
 use std::collections::HashMap;
 
-/// Symbol table, containing a hashmap mapping String names
-/// to Symbols.
-#[derive(Debug, Clone, Default)]
+/// Symbol table, containing a stack of scopes mapping String
+/// names to Symbols : inner declarations shadow the outer ones.
+#[derive(Debug, Clone)]
 pub struct SymbolTable {
-    table: HashMap<String, Symbol>,
+    scopes: Vec<HashMap<String, Symbol>>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        SymbolTable { scopes: vec![HashMap::new()] }
+    }
 }
 
 impl SymbolTable {
+    /// innermost declaration of `name`.
     pub fn get(&self, name: &str) -> Option<&Symbol> {
-        self.table.get(name)
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// depth of the current scope, the global one being 0.
+    pub fn depth(&self) -> usize {
+        self.scopes.len() - 1
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn exit_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
     }
 
     pub fn insert_builder(
@@ -26,31 +48,35 @@ impl SymbolTable {
         s: SymbolBuilder,
         lexer: &dyn NonStreamingLexer<DefaultLexerTypes>,
     ) -> Result<(), String> {
+        let depth = self.depth();
         let name = lexer.span_str(s.get_name());
-        if self.table.contains_key(name) {
-            return Err(format!("Variable declared multiple times"));
+        if self.scopes[depth].contains_key(name) {
+            return Err("Variable declared multiple times".to_string());
         }
-        let symb = s.build(lexer).unwrap();
-        self.table.insert(name.to_string(), symb.clone());
+        let symb = s.build(lexer, depth).unwrap();
+        self.scopes[depth].insert(name.to_string(), symb.clone());
         Ok(())
     }
 
     pub fn insert_symbol(&mut self, s: Symbol, check: bool) -> Result<(), String> {
-        if check && self.table.contains_key(s.get_name()) {
-            return Err(format!("Multiple variables with same name defined"));
+        let depth = self.depth();
+        if check && self.scopes[depth].contains_key(s.get_name()) {
+            return Err("Multiple variables with same name defined".to_string());
         }
-        self.table.insert(s.get_name().to_string(), s);
+        self.scopes[depth].insert(s.get_name().to_string(), s);
         Ok(())
     }
 }
 
 /// Enumeration storing data about all the symbols,
-/// currently, only variables are supported.
+/// currently, only variables are supported. The depth of the
+/// scope of the declaration tells shadowed variables apart.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Symbol {
     Variable {
         name: String,
         dtype: Type,
+        depth: usize,
     },
 }
 
@@ -66,6 +92,12 @@ impl Symbol {
             Self::Variable { dtype, .. } => dtype,
         }
     }
+
+    pub fn get_depth(&self) -> usize {
+        match self {
+            Self::Variable { depth, .. } => *depth,
+        }
+    }
 }
 
 impl PartialOrd for Symbol {
@@ -75,13 +107,13 @@ impl PartialOrd for Symbol {
 }
 
 impl Ord for Symbol {
-    // we compare symbols per lexical order
+    // we compare symbols per lexical order, then per depth
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         match self {
-            Symbol::Variable { name : n1, dtype : _ } => {
+            Symbol::Variable { name : n1, dtype : _, depth : d1 } => {
                 match other {
-                    Symbol::Variable { name : n2, dtype : _ }
-                        => n1.cmp(n2)
+                    Symbol::Variable { name : n2, dtype : _, depth : d2 }
+                        => n1.cmp(n2).then(d1.cmp(d2))
                 }
             },
         }
@@ -143,10 +175,12 @@ impl SymbolBuilder {
     pub fn build(
         self,
         lexer: &dyn NonStreamingLexer<DefaultLexerTypes>,
+        depth: usize,
     ) -> Result<Symbol, String> {
         Ok(Symbol::Variable {
             name: lexer.span_str(self.name).to_string(),
             dtype: self.dtype.build()?,
+            depth,
         })
     }
 }
\ No newline at end of file
@@ -0,0 +1,6 @@
+{
+  int x;
+  x = 0;
+  while (x < 10) x = x + 1;
+  print(x);
+}
@@ -0,0 +1,6 @@
+{
+  int x;
+  x = 0;
+  while (x <= 20) x = x + rand(1,3);
+  print(x);
+}
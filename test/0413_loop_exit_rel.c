@@ -0,0 +1,8 @@
+{
+  int N;
+  int x;
+  N = rand(0,50);
+  x = 0;
+  while (x < N) x = x + 1;
+  print(x,N);
+}